@@ -41,18 +41,25 @@ impl Cell {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-enum Logic {
+/// Families of logical techniques that a solver can be asked not to use.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Logic {
+    /// Naked and hidden pairs, triples and quads.
     #[serde(rename = "tuples")]
     Tuples,
+    /// Pointing and claiming.
     #[serde(rename = "pointing")]
     Pointing,
+    /// X-Wings, Swordfish, and larger fish.
     #[serde(rename = "fishes")]
     Fishes,
+    /// XY-Wings, XYZ-Wings and similar.
     #[serde(rename = "wings")]
     Wings,
+    /// Alternating inference chains.
     #[serde(rename = "aic")]
     Aic,
+    /// Finding contradictions by trying candidates.
     #[serde(rename = "contradictions")]
     Contradictions,
 }
@@ -65,9 +72,29 @@ enum TrueCandidatesOption {
     Logical,
 }
 
+/// A clue that sits between two orthogonally adjacent cells.
 #[derive(Debug, Deserialize, Serialize)]
-struct CellPair {
-    cells: [String; 2],
+pub struct CellPair {
+    /// The two cells the clue sits between.
+    pub cells: [String; 2],
+    /// The value of the clue. When this is missing, the default for the type of clue is used.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// Clue types where every clue has been given, so cells without a clue can't satisfy it.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub enum Negative {
+    /// All differences (white dots) are given.
+    #[serde(rename = "difference")]
+    Difference,
+    /// All ratios (black dots) are given.
+    #[serde(rename = "ratio")]
+    Ratio,
+    /// All X and V clues are given.
+    #[serde(rename = "xv")]
+    Xv,
 }
 
 /// A representation of a quadruple clue: the values must show up in the four cells listed.
@@ -79,16 +106,126 @@ pub struct Quad {
     pub values: Vec<usize>,
 }
 
+/// The two kinds of XV clue.
 #[derive(Debug, Deserialize, Serialize)]
-enum Xv {
+pub enum Xv {
+    /// The two digits add up to 10.
     X,
+    /// The two digits add up to 5.
     V,
 }
 
+/// An X or V between two orthogonally adjacent cells.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct XvClue {
+    /// The two cells the clue sits between.
+    pub cells: [String; 2],
+    /// Whether the clue is an X or a V.
+    pub value: Xv,
+}
+
+/// A killer cage: digits can't repeat in the cage, and they add up to the value.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Cage {
+    /// The cells in the cage.
+    pub cells: Vec<String>,
+    /// The sum of the digits in the cage. When this is missing, the digits only have to be
+    /// distinct.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// A clue drawn as one or more lines through the grid. Each line is a list of RC notated cells,
+/// in order.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Line {
+    /// The lines that make up the clue.
+    pub lines: Vec<Vec<String>>,
+}
+
+/// A line drawn on the grid. f-puzzles doesn't give these a meaning, so the outline colour is used
+/// to tell which kind of line it is.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CustomLine {
+    /// The lines that make up the clue.
+    pub lines: Vec<Vec<String>>,
+    /// The colour of the line, as a hex string like `#67F067`.
+    #[serde(rename = "outlineC")]
+    #[serde(default)]
+    pub outline_c: String,
+    /// A parameter for the line, for kinds of line that take one.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// An arrow: the digits along the arrow add up to the number in the circle.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Arrow {
+    /// The lines of the arrow. Each line starts in the circle.
+    pub lines: Vec<Vec<String>>,
+    /// The cells of the circle. When there is more than one, their digits are read as a number.
+    pub cells: Vec<String>,
+}
+
+/// A clue outside the grid. Rows and columns are numbered from 1, so the cell is in row or column
+/// 0, or one past the size of the grid.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OutsideClue {
+    /// The RC notated cell the clue is written in.
+    pub cell: String,
+    /// The value of the clue.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// The direction a little killer clue points in.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Diagonal {
+    /// Up and to the right.
+    #[serde(rename = "UR")]
+    UpRight,
+    /// Up and to the left.
+    #[serde(rename = "UL")]
+    UpLeft,
+    /// Down and to the right.
+    #[serde(rename = "DR")]
+    DownRight,
+    /// Down and to the left.
+    #[serde(rename = "DL")]
+    DownLeft,
+}
+
+/// A little killer clue: the digits along the diagonal add up to the value. Digits may repeat.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LittleKiller {
+    /// The RC notated cell outside the grid the clue is written in.
+    pub cell: String,
+    /// The direction of the diagonal, starting from the clue.
+    pub direction: Diagonal,
+    /// The sum of the digits along the diagonal.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// Two groups of cells with the same shape, which hold the same digits in matching positions.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CloneRegion {
+    /// The RC notated cells of the first group.
+    pub cells: Vec<String>,
+    /// The RC notated cells of the second group, in the same order as the first.
+    #[serde(rename = "cloneCells")]
+    pub clone_cells: Vec<String>,
+}
+
+/// A marker drawn in a single cell.
 #[derive(Debug, Deserialize, Serialize)]
-struct XvClue {
-    cells: [String; 2],
-    value: Xv,
+pub struct Marker {
+    /// The RC notated cell that is marked.
+    pub cell: String,
 }
 
 /// A list of RC notated cells.
@@ -121,22 +258,32 @@ pub struct FPuzzles {
     #[serde(default)]
     pub negative_diagonal: bool,
 
+    /// `true` indicates that digits can't repeat a chess knight's move apart.
     #[serde(default)]
-    antiknight: bool,
+    pub antiknight: bool,
+    /// `true` indicates that digits can't repeat a chess king's move apart.
     #[serde(default)]
-    antiking: bool,
+    pub antiking: bool,
+    /// `true` indicates that digits can't repeat among cells in the same position of different
+    /// boxes.
     #[serde(default)]
-    disjointgroups: bool,
+    pub disjointgroups: bool,
+    /// `true` indicates that orthogonally adjacent cells can't contain consecutive digits.
     #[serde(default)]
-    nonconsecutive: bool,
+    pub nonconsecutive: bool,
+    /// Logical techniques that shouldn't be used when solving.
     #[serde(default)]
-    disabledlogic: Vec<Logic>,
+    pub disabledlogic: Vec<Logic>,
     #[serde(default)]
     truecandidatesoptions: Vec<TrueCandidatesOption>,
+    /// Kropki white dots: the digits in the two cells differ by the value of the clue, or 1 if no
+    /// value is given.
     #[serde(default)]
-    difference: Vec<CellPair>,
+    pub difference: Vec<CellPair>,
+    /// Kropki black dots: one digit in the two cells is the value of the clue times the other, or
+    /// twice the other if no value is given.
     #[serde(default)]
-    ratio: Vec<CellPair>,
+    pub ratio: Vec<CellPair>,
 
     /// A list of quadruple clues.
     #[serde(default)]
@@ -148,15 +295,83 @@ pub struct FPuzzles {
     author: String,
     #[serde(default)]
     ruleset: String,
+    /// XV clues: the digits in the two cells add up to 10 for an X, or 5 for a V.
+    #[serde(default)]
+    pub xv: Vec<XvClue>,
+
+    /// A list of killer cages.
+    #[serde(default)]
+    pub killercage: Vec<Cage>,
+
+    /// Thermometers: digits strictly increase along each line, starting from the bulb.
+    #[serde(default)]
+    pub thermometer: Vec<Line>,
+
+    /// Palindromes: each line reads the same in both directions.
+    #[serde(default)]
+    pub palindrome: Vec<Line>,
+
+    /// Between lines: the digits on each line are strictly between the digits in the circles at
+    /// its ends.
+    #[serde(default)]
+    pub betweenline: Vec<Line>,
+
+    /// A list of arrows.
+    #[serde(default)]
+    pub arrow: Vec<Arrow>,
+
+    /// Sandwich sums: the digits between the lowest and highest digits of a row or column add up
+    /// to the value.
+    #[serde(default)]
+    pub sandwichsum: Vec<OutsideClue>,
+
+    /// X-sum clues: the sum of the first X digits seen from the clue, where X is the first digit.
+    #[serde(default)]
+    pub xsum: Vec<OutsideClue>,
+
+    /// Skyscraper clues: how many digits are seen from the clue, when taller digits hide shorter
+    /// ones behind them.
+    #[serde(default)]
+    pub skyscraper: Vec<OutsideClue>,
+
+    /// A list of little killer clues.
     #[serde(default)]
-    xv: Vec<XvClue>,
+    pub littlekillersum: Vec<LittleKiller>,
+
+    /// Lines drawn on the grid, which are told apart by their colour.
+    #[serde(default)]
+    pub line: Vec<CustomLine>,
 
     /// An extra region in the grid where digits cannot repeat.
     #[serde(default)]
     pub extraregion: Vec<Region>,
 
+    /// Pairs of groups of cells that are copies of each other.
+    #[serde(default)]
+    pub clone: Vec<CloneRegion>,
+
+    /// Cells that contain an odd digit.
+    #[serde(default)]
+    pub odd: Vec<Marker>,
+
+    /// Cells that contain an even digit.
+    #[serde(default)]
+    pub even: Vec<Marker>,
+
+    /// Cells that are smaller than all of their orthogonal neighbours.
+    #[serde(default)]
+    pub minimum: Vec<Marker>,
+
+    /// Cells that are larger than all of their orthogonal neighbours.
+    #[serde(default)]
+    pub maximum: Vec<Marker>,
+
     #[serde(default)]
     solution: Vec<usize>,
+
+    /// Clue types where every clue has been given.
+    #[serde(default)]
+    pub negative: Vec<Negative>,
 }
 
 impl FPuzzles {
@@ -181,8 +396,24 @@ impl FPuzzles {
             author: String::new(),
             ruleset: String::new(),
             xv: Vec::new(),
+            killercage: Vec::new(),
+            thermometer: Vec::new(),
+            palindrome: Vec::new(),
+            betweenline: Vec::new(),
+            arrow: Vec::new(),
+            sandwichsum: Vec::new(),
+            xsum: Vec::new(),
+            skyscraper: Vec::new(),
+            littlekillersum: Vec::new(),
+            line: Vec::new(),
             extraregion: Vec::new(),
+            clone: Vec::new(),
+            odd: Vec::new(),
+            even: Vec::new(),
+            minimum: Vec::new(),
+            maximum: Vec::new(),
             solution: Vec::new(),
+            negative: Vec::new(),
         }
     }
 
@@ -264,6 +495,160 @@ mod tests {
         assert!(f.is_err());
     }
 
+    #[test]
+    fn difference() {
+        let f: FPuzzles = serde_json::from_str(
+            r#"{"size":4,"grid":[],"difference":[{"cells":["R1C1","R1C2"]},{"cells":["R2C1","R2C2"],"value":"2"}],"negative":["difference"]}"#,
+        )
+        .unwrap();
+        assert_eq!(f.difference.len(), 2);
+        assert_eq!(f.difference[0].value, None);
+        assert_eq!(f.difference[1].value, Some("2".to_string()));
+        assert_eq!(f.negative, vec![Negative::Difference]);
+    }
+
+    #[test]
+    fn xv() {
+        let f: FPuzzles = serde_json::from_str(
+            r#"{"size":4,"grid":[],"xv":[{"cells":["R1C1","R1C2"],"value":"X"},{"cells":["R2C1","R2C2"],"value":"V"}],"negative":["xv"]}"#,
+        )
+        .unwrap();
+        assert!(matches!(f.xv[0].value, Xv::X));
+        assert!(matches!(f.xv[1].value, Xv::V));
+        assert_eq!(f.negative, vec![Negative::Xv]);
+    }
+
+    #[test]
+    fn killercage() {
+        let f: FPuzzles = serde_json::from_str(
+            r#"{"size":4,"grid":[],"killercage":[{"cells":["R1C1","R1C2"],"value":"3"},{"cells":["R2C1","R3C1"]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(f.killercage.len(), 2);
+        assert_eq!(f.killercage[0].cells, vec!["R1C1", "R1C2"]);
+        assert_eq!(f.killercage[0].value, Some("3".to_string()));
+        assert_eq!(f.killercage[1].value, None);
+    }
+
+    #[test]
+    fn thermometer() {
+        let f: FPuzzles = serde_json::from_str(
+            r#"{"size":4,"grid":[],"thermometer":[{"lines":[["R1C1","R1C2","R2C2"],["R1C1","R2C1"]]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(f.thermometer.len(), 1);
+        assert_eq!(f.thermometer[0].lines[0], vec!["R1C1", "R1C2", "R2C2"]);
+        assert_eq!(f.thermometer[0].lines[1], vec!["R1C1", "R2C1"]);
+    }
+
+    #[test]
+    fn palindrome() {
+        let f: FPuzzles = serde_json::from_str(
+            r#"{"size":4,"grid":[],"palindrome":[{"lines":[["R1C1","R2C2","R3C3"]]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(f.palindrome[0].lines[0], vec!["R1C1", "R2C2", "R3C3"]);
+    }
+
+    #[test]
+    fn betweenline() {
+        let f: FPuzzles = serde_json::from_str(
+            r#"{"size":4,"grid":[],"betweenline":[{"lines":[["R1C1","R1C2","R1C3"]]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(f.betweenline[0].lines[0], vec!["R1C1", "R1C2", "R1C3"]);
+    }
+
+    #[test]
+    fn disabledlogic() {
+        let f: FPuzzles =
+            serde_json::from_str(r#"{"size":4,"grid":[],"disabledlogic":["tuples","fishes"]}"#)
+                .unwrap();
+        assert_eq!(f.disabledlogic, vec![Logic::Tuples, Logic::Fishes]);
+    }
+
+    #[test]
+    fn markers() {
+        let f: FPuzzles = serde_json::from_str(
+            r#"{"size":4,"grid":[],"odd":[{"cell":"R1C1"}],"even":[{"cell":"R1C2"}],
+            "minimum":[{"cell":"R2C1"}],"maximum":[{"cell":"R2C2"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(f.odd[0].cell, "R1C1");
+        assert_eq!(f.even[0].cell, "R1C2");
+        assert_eq!(f.minimum[0].cell, "R2C1");
+        assert_eq!(f.maximum[0].cell, "R2C2");
+    }
+
+    #[test]
+    fn clone() {
+        let f: FPuzzles = serde_json::from_str(
+            r#"{"size":4,"grid":[],"clone":[{"cells":["R1C1","R1C2"],"cloneCells":["R3C3","R3C4"]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(f.clone[0].cells, vec!["R1C1", "R1C2"]);
+        assert_eq!(f.clone[0].clone_cells, vec!["R3C3", "R3C4"]);
+    }
+
+    #[test]
+    fn xsum_and_skyscraper() {
+        let f: FPuzzles = serde_json::from_str(
+            r#"{"size":4,"grid":[],"xsum":[{"cell":"R0C1","value":"7"}],
+            "skyscraper":[{"cell":"R2C5","value":"3"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(f.xsum[0].cell, "R0C1");
+        assert_eq!(f.xsum[0].value, Some("7".to_string()));
+        assert_eq!(f.skyscraper[0].cell, "R2C5");
+        assert_eq!(f.skyscraper[0].value, Some("3".to_string()));
+    }
+
+    #[test]
+    fn arrow() {
+        let f: FPuzzles = serde_json::from_str(
+            r#"{"size":9,"grid":[],"arrow":[{"lines":[["R1C2","R2C3","R3C3"]],"cells":["R1C1","R1C2"]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(f.arrow.len(), 1);
+        assert_eq!(f.arrow[0].cells, vec!["R1C1", "R1C2"]);
+        assert_eq!(f.arrow[0].lines[0], vec!["R1C2", "R2C3", "R3C3"]);
+    }
+
+    #[test]
+    fn sandwichsum() {
+        let f: FPuzzles = serde_json::from_str(
+            r#"{"size":9,"grid":[],"sandwichsum":[{"cell":"R0C1","value":"10"},{"cell":"R4C0"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(f.sandwichsum[0].cell, "R0C1");
+        assert_eq!(f.sandwichsum[0].value, Some("10".to_string()));
+        assert_eq!(f.sandwichsum[1].value, None);
+    }
+
+    #[test]
+    fn littlekillersum() {
+        let f: FPuzzles = serde_json::from_str(
+            r#"{"size":9,"grid":[],"littlekillersum":[{"cell":"R0C1","direction":"DR","value":"45"},{"cell":"R10C3","direction":"UL"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(f.littlekillersum[0].cell, "R0C1");
+        assert_eq!(f.littlekillersum[0].direction, Diagonal::DownRight);
+        assert_eq!(f.littlekillersum[0].value, Some("45".to_string()));
+        assert_eq!(f.littlekillersum[1].direction, Diagonal::UpLeft);
+        assert_eq!(f.littlekillersum[1].value, None);
+    }
+
+    #[test]
+    fn line() {
+        let f: FPuzzles = serde_json::from_str(
+            r##"{"size":9,"grid":[],"line":[{"lines":[["R1C1","R1C2"]],"outlineC":"#67F067","width":0.3,"isNewConstraint":true}]}"##,
+        )
+        .unwrap();
+        assert_eq!(f.line[0].lines[0], vec!["R1C1", "R1C2"]);
+        assert_eq!(f.line[0].outline_c, "#67F067");
+        assert_eq!(f.line[0].value, None);
+    }
+
     #[test]
     fn bad_digit() {
         let f = FPuzzles::try_from("12.......k......");
@@ -1,9 +1,9 @@
 #![warn(missing_docs)]
 
-use crate::types::{Command, Error, Request, Response};
+use crate::types::{Command, Error, LogicalCell, Request, Response};
 use f_puzzles::FPuzzles;
 use rayon::spawn;
-use solution_iter::Solvable;
+use solution_iter::{SolutionIterator, Solvable};
 use sudoku_engine::Board;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::error::TrySendError;
@@ -121,11 +121,11 @@ fn true_candidates(
     };
 
     let Some(result) = solution_iter::true_candidates_bfs(&b, token) else {
-            while let Err(TrySendError::Full(_)) = ch_tx.try_send(Response::Invalid {
-                    nonce,
-                    message: "ERROR: The givens are invalid (no solutions).".to_string()
-                }) {};
-            return;
+        while let Err(TrySendError::Full(_)) = ch_tx.try_send(Response::Invalid {
+            nonce,
+            message: "ERROR: The givens are invalid (no solutions).".to_string(),
+        }) {}
+        return;
     };
 
     let size = b.size();
@@ -144,6 +144,142 @@ fn true_candidates(
     }) {}
 }
 
+fn solve(
+    nonce: usize,
+    f_puz: &FPuzzles,
+    token: &CancellationToken,
+    ch_tx: &mpsc::Sender<Response>,
+) {
+    let b = match Board::try_from(f_puz) {
+        Ok(b) => b,
+        Err(e) => {
+            while let Err(TrySendError::Full(_)) = ch_tx.try_send(Response::Invalid {
+                nonce,
+                message: e.to_string(),
+            }) {}
+            return;
+        }
+    };
+    let mut solns = SolutionIterator::with_token(&b, token);
+    let Some(solution) = solns.next() else {
+        if token.is_cancelled() {
+            return;
+        }
+        while let Err(TrySendError::Full(_)) = ch_tx.try_send(Response::Invalid {
+            nonce,
+            message: "ERROR: The givens are invalid (no solutions).".to_string(),
+        }) {}
+        return;
+    };
+    if token.is_cancelled() {
+        return;
+    }
+    let response = if solns.next().is_none() {
+        Response::Solved {
+            nonce,
+            solution: solution.digits(),
+        }
+    } else {
+        Response::Invalid {
+            nonce,
+            message: "ERROR: The puzzle has multiple solutions.".to_string(),
+        }
+    };
+    if token.is_cancelled() {
+        return;
+    }
+    while let Err(TrySendError::Full(_)) = ch_tx.try_send(response.clone()) {}
+}
+
+fn logical_response(nonce: usize, b: &Board, message: String, is_valid: bool) -> Response {
+    let cells = b
+        .digits()
+        .into_iter()
+        .enumerate()
+        .map(|(idx, value)| LogicalCell {
+            value,
+            candidates: b.candidates(idx),
+        })
+        .collect();
+    Response::LogicalResponse {
+        nonce,
+        cells,
+        message,
+        is_valid,
+    }
+}
+
+fn logical_status(b: &Board) -> String {
+    if b.solved() {
+        "Solved!".to_string()
+    } else {
+        "No logical steps found.".to_string()
+    }
+}
+
+fn logical_step(
+    nonce: usize,
+    f_puz: &FPuzzles,
+    token: &CancellationToken,
+    ch_tx: &mpsc::Sender<Response>,
+) {
+    let mut b = match Board::try_from(f_puz) {
+        Ok(b) => b,
+        Err(e) => {
+            while let Err(TrySendError::Full(_)) = ch_tx.try_send(Response::Invalid {
+                nonce,
+                message: e.to_string(),
+            }) {}
+            return;
+        }
+    };
+    let response = match b.logical_step() {
+        Ok(Some(step)) => logical_response(nonce, &b, step, true),
+        Ok(None) => logical_response(nonce, &b, logical_status(&b), true),
+        Err(_) => logical_response(
+            nonce,
+            &b,
+            "The puzzle has a contradiction.".to_string(),
+            false,
+        ),
+    };
+    if token.is_cancelled() {
+        return;
+    }
+    while let Err(TrySendError::Full(_)) = ch_tx.try_send(response.clone()) {}
+}
+
+fn solve_path(
+    nonce: usize,
+    f_puz: &FPuzzles,
+    token: &CancellationToken,
+    ch_tx: &mpsc::Sender<Response>,
+) {
+    let mut b = match Board::try_from(f_puz) {
+        Ok(b) => b,
+        Err(e) => {
+            while let Err(TrySendError::Full(_)) = ch_tx.try_send(Response::Invalid {
+                nonce,
+                message: e.to_string(),
+            }) {}
+            return;
+        }
+    };
+    let mut steps = Vec::new();
+    let is_valid = if b.logical_solve(token, &mut steps).is_ok() {
+        steps.push(logical_status(&b));
+        true
+    } else {
+        steps.push("The puzzle has a contradiction.".to_string());
+        false
+    };
+    if token.is_cancelled() {
+        return;
+    }
+    let response = logical_response(nonce, &b, steps.join("\n"), is_valid);
+    while let Err(TrySendError::Full(_)) = ch_tx.try_send(response.clone()) {}
+}
+
 async fn process_fpuzzles_data(
     nonce: usize,
     command: &Command,
@@ -183,8 +319,14 @@ async fn process_fpuzzles_data(
         Command::TrueCandidates => {
             spawn(move || true_candidates(nonce, &f_puz, &token, &ch_tx));
         }
-        _ => {
-            todo!();
+        Command::Solve => {
+            spawn(move || solve(nonce, &f_puz, &token, &ch_tx));
+        }
+        Command::Step => {
+            spawn(move || logical_step(nonce, &f_puz, &token, &ch_tx));
+        }
+        Command::SolvePath => {
+            spawn(move || solve_path(nonce, &f_puz, &token, &ch_tx));
         }
     }
     Ok(())
@@ -402,6 +544,141 @@ mod tests {
         );
     }
 
+    #[test]
+    fn solve_puzzle() {
+        let res_f = FPuzzles::try_from(
+            "19..7..5....28..........37.2.5.....4...4.5.....6.....9731....2....82.....4....91.",
+        );
+        assert!(res_f.is_ok());
+        let f = res_f.unwrap();
+        let token = CancellationToken::new();
+        let (ch_tx, mut ch_rx) = mpsc::channel::<Response>(1);
+        solve(42, &f, &token, &ch_tx);
+        let response = ch_rx.try_recv();
+        assert!(response.is_ok());
+        let Response::Solved { nonce, solution } = response.unwrap() else {
+            panic!("Expected a solution.");
+        };
+        assert_eq!(nonce, 42);
+        assert_eq!(solution.len(), 81);
+        assert_eq!(solution[0], 1);
+        assert_eq!(solution[1], 9);
+        assert!(solution.iter().all(|d| (1..=9).contains(d)));
+
+        token.cancel();
+        solve(43, &f, &token, &ch_tx);
+        assert_eq!(ch_rx.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn solve_puzzle_multiple_solutions() {
+        let res_f = FPuzzles::try_from(
+            ".9..7..5....28..........37.2.5.....4...4.5.....6.....9731....2....82.....4....91.",
+        );
+        assert!(res_f.is_ok());
+        let f = res_f.unwrap();
+        let token = CancellationToken::new();
+        let (ch_tx, mut ch_rx) = mpsc::channel::<Response>(1);
+        solve(42, &f, &token, &ch_tx);
+        let response = ch_rx.try_recv();
+        assert!(response.is_ok());
+        assert_eq!(
+            response.unwrap(),
+            Response::Invalid {
+                nonce: 42,
+                message: "ERROR: The puzzle has multiple solutions.".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn solve_puzzle_no_solutions() {
+        let res_f = FPuzzles::try_from(
+            // The givens don't clash, but the 2 in R1C3 leaves no solution.
+            "192.7..5....28..........37.2.5.....4...4.5.....6.....9731....2....82.....4....91.",
+        );
+        assert!(res_f.is_ok());
+        let f = res_f.unwrap();
+        let token = CancellationToken::new();
+        let (ch_tx, mut ch_rx) = mpsc::channel::<Response>(1);
+        solve(42, &f, &token, &ch_tx);
+        let response = ch_rx.try_recv();
+        assert!(response.is_ok());
+        assert_eq!(
+            response.unwrap(),
+            Response::Invalid {
+                nonce: 42,
+                message: "ERROR: The givens are invalid (no solutions).".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn step_puzzle() {
+        let res_f = FPuzzles::try_from(
+            "12345678.........................................................................",
+        );
+        assert!(res_f.is_ok());
+        let f = res_f.unwrap();
+        let token = CancellationToken::new();
+        let (ch_tx, mut ch_rx) = mpsc::channel::<Response>(1);
+        logical_step(42, &f, &token, &ch_tx);
+        let response = ch_rx.try_recv();
+        assert!(response.is_ok());
+        let Response::LogicalResponse {
+            nonce,
+            cells,
+            message,
+            is_valid,
+        } = response.unwrap()
+        else {
+            panic!("Expected a logical response.");
+        };
+        assert_eq!(nonce, 42);
+        assert!(is_valid);
+        assert_eq!(message, "Naked single 9 at R1C9");
+        assert_eq!(cells.len(), 81);
+        assert_eq!(
+            cells[8],
+            LogicalCell {
+                value: 9,
+                candidates: vec![9]
+            }
+        );
+        assert_eq!(cells[9].value, 0);
+        assert_eq!(cells[9].candidates, vec![4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn solve_path_puzzle() {
+        let res_f = FPuzzles::try_from(
+            "19..7..5....28..........37.2.5.....4...4.5.....6.....9731....2....82.....4....91.",
+        );
+        assert!(res_f.is_ok());
+        let f = res_f.unwrap();
+        let token = CancellationToken::new();
+        let (ch_tx, mut ch_rx) = mpsc::channel::<Response>(1);
+        solve_path(42, &f, &token, &ch_tx);
+        let response = ch_rx.try_recv();
+        assert!(response.is_ok());
+        let Response::LogicalResponse {
+            cells,
+            message,
+            is_valid,
+            ..
+        } = response.unwrap()
+        else {
+            panic!("Expected a logical response.");
+        };
+        assert!(is_valid);
+        assert!(message.ends_with("Solved!"));
+        assert!(cells.iter().all(|c| c.value != 0));
+
+        token.cancel();
+        solve_path(43, &f, &token, &ch_tx);
+        assert_eq!(ch_rx.try_recv(), Err(TryRecvError::Empty));
+    }
+
     #[tokio::test]
     async fn process_fpuzzles_data_test() {
         let res_f = FPuzzles::try_from(
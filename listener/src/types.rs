@@ -36,14 +36,15 @@ pub(crate) enum Request {
     },
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct LogicalCell {
-    value: usize,
-    candidates: Vec<usize>,
+    pub(crate) value: usize,
+    pub(crate) candidates: Vec<usize>,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 #[serde(tag = "type")]
+#[allow(clippy::enum_variant_names)]
 pub enum Response {
     #[serde(rename = "cancelled")]
     Cancelled { nonce: usize },
@@ -57,7 +58,6 @@ pub enum Response {
         solutions_per_candidate: Vec<usize>,
     },
     #[serde(rename = "solved")]
-    #[allow(dead_code)]
     Solved { nonce: usize, solution: Vec<usize> },
     #[serde(rename = "count")]
     Count {
@@ -67,7 +67,6 @@ pub enum Response {
         in_progress: bool,
     },
     #[serde(rename = "logical")]
-    #[allow(dead_code)]
     LogicalResponse {
         nonce: usize,
         cells: Vec<LogicalCell>,
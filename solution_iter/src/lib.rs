@@ -38,6 +38,7 @@ pub trait Solvable: Clone {
 /// An iter that will generate solutions to the puzzle.
 pub struct SolutionIterator<T: Solvable> {
     stack: Vec<(T, usize, Vec<T::Guess>)>,
+    token: Option<CancellationToken>,
 }
 
 impl<T: Solvable> SolutionIterator<T> {
@@ -51,22 +52,39 @@ impl<T: Solvable> SolutionIterator<T> {
                     if board.solved() {
                         Self {
                             stack: vec![(board, 0, Vec::new())],
+                            token: None,
                         }
                     } else {
-                        Self { stack: Vec::new() }
+                        Self {
+                            stack: Vec::new(),
+                            token: None,
+                        }
                     }
                 }
                 Some(next_idx) => {
                     let values = board.guesses(next_idx);
                     Self {
                         stack: vec![(board, next_idx, values)],
+                        token: None,
                     }
                 }
             }
         } else {
-            Self { stack: Vec::new() }
+            Self {
+                stack: Vec::new(),
+                token: None,
+            }
         }
     }
+
+    /// Create a new `SolutionIterator` that stops looking for solutions once `token` is
+    /// cancelled. The search is checked between guesses, so cancelling stops it part way through,
+    /// not only between solutions.
+    pub fn with_token(b: &T, token: &CancellationToken) -> Self {
+        let mut ret = Self::new(b);
+        ret.token = Some(token.clone());
+        ret
+    }
 }
 
 impl<T: Solvable + Debug> std::iter::Iterator for SolutionIterator<T> {
@@ -74,6 +92,13 @@ impl<T: Solvable + Debug> std::iter::Iterator for SolutionIterator<T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         while !self.stack.is_empty() {
+            if self
+                .token
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled)
+            {
+                return None;
+            }
             let (mut board, next_idx, mut values) = self.stack.pop()?;
 
             if board.solved() {
@@ -122,9 +147,7 @@ impl<T: Solvable + Debug> std::iter::Iterator for SolutionIterator<T> {
 /// factors that have many solutions available.
 pub fn true_candidates_dfs<T: Solvable + BitOrAssign + Debug>(puzzle: &T) -> Option<T> {
     let mut iter = SolutionIterator::new(puzzle);
-    let Some(mut ret) = iter.next() else {
-        return None;
-    };
+    let mut ret = iter.next()?;
     for sln in iter {
         ret |= sln;
     }
@@ -178,9 +201,7 @@ pub fn true_candidates_bfs<T: Solvable + BitOrAssign + Debug>(
     puzzle: &T,
     token: &CancellationToken,
 ) -> Option<T> {
-    let Some(mut ret) = SolutionIterator::new(puzzle).next() else {
-        return None;
-    };
+    let mut ret = SolutionIterator::new(puzzle).next()?;
 
     tc_breadth_first(puzzle.clone(), &mut ret, token);
     Some(ret)
@@ -199,9 +220,7 @@ pub fn true_candidates<T: Solvable + BitOrAssign + Debug>(
     token: &CancellationToken,
 ) -> Option<T> {
     let mut iter = SolutionIterator::new(puzzle);
-    let Some(mut ret) = iter.next() else {
-        return None;
-    };
+    let mut ret = iter.next()?;
     let mut count = 1;
     for sln in iter {
         ret |= sln;
@@ -2,12 +2,12 @@
 
 use crate::constraints;
 use crate::types::{
-    to_bits, Bits, Board, BoardMeta, Constraint, Contradiction, Elimination, MoreBits,
-    SudokuErrors, DIMENSIONS,
+    to_bits, Bits, Board, BoardMeta, Constraint, Contradiction, Deductions, Elimination, ExtraLine,
+    Line, MoreBits, Overlap, Sightline, SudokuErrors, DIMENSIONS,
 };
 use core::iter::Iterator;
 use core::ops::BitOrAssign;
-use f_puzzles::FPuzzles;
+use f_puzzles::{CellPair, CustomLine, Diagonal, FPuzzles, Logic, Negative, Xv};
 use itertools::Itertools;
 use rayon::prelude::*;
 use solution_iter::SolutionIterator;
@@ -74,14 +74,6 @@ impl Board {
         Self::new_with_regions(size, max_val, build_default_regions(size)?, Vec::new())
     }
 
-    fn new_with_constraints(
-        size: usize,
-        max_val: usize,
-        constraints: Vec<Constraint>,
-    ) -> Result<Self, SudokuErrors> {
-        Self::new_with_regions(size, max_val, build_default_regions(size)?, constraints)
-    }
-
     pub(crate) fn new_with_regions(
         size: usize,
         max_val: usize,
@@ -106,30 +98,49 @@ impl Board {
             columns.push((c..size * size).step_by(size).collect());
         }
 
+        let mut meta = Arc::new(BoardMeta {
+            size,
+            max_val,
+            rows,
+            columns,
+            regions,
+            constraints,
+            overlapping_units: Vec::new(),
+            unit_cells: Vec::new(),
+            peers: Vec::new(),
+            deductions: Deductions::default(),
+        });
+        let overlapping_units = Self::overlapping_units(&meta);
+        let unit_cells = Self::unit_cells(&meta);
+        let peers = Self::peers(&meta, &unit_cells);
+        if let Some(m) = Arc::get_mut(&mut meta) {
+            m.overlapping_units = overlapping_units;
+            m.unit_cells = unit_cells;
+            m.peers = peers;
+        }
+
         let mut b = Board {
             used_digits: 0,
             solved_digits: MoreBits::ZERO,
             grid,
-            meta: Arc::new(BoardMeta {
-                size,
-                max_val,
-                rows,
-                columns,
-                regions,
-                constraints,
-            }),
+            meta,
         };
 
+        b.init_constraints()?;
+        Ok(b)
+    }
+
+    /// Prune the candidates with every constraint, until none of them makes progress.
+    fn init_constraints(&mut self) -> Result<(), Contradiction> {
         let mut init_status = Elimination::Eliminated;
-        let meta = b.meta.clone();
+        let meta = self.meta.clone();
         while init_status == Elimination::Eliminated {
             init_status = Elimination::Same;
             for c in &meta.constraints {
-                init_status &= b.init_constraint(c)?;
+                init_status &= self.init_constraint(c)?;
             }
         }
-
-        Ok(b)
+        Ok(())
     }
 
     pub(crate) fn from_digits(
@@ -299,6 +310,73 @@ impl Board {
                 constraints::check_quad(*idx, *single, *double, self.meta.size, &self.grid)
             }
             Constraint::Region(region) => constraints::check_region(region, &self.grid),
+            Constraint::AntiKnight => {
+                constraints::check_offsets(&constraints::KNIGHT_OFFSETS, self.meta.size, &self.grid)
+            }
+            Constraint::AntiKing => {
+                constraints::check_offsets(&constraints::KING_OFFSETS, self.meta.size, &self.grid)
+            }
+            Constraint::Nonconsecutive => {
+                constraints::check_nonconsecutive(self.meta.size, &self.grid)
+            }
+            Constraint::Difference(a, b, diff) => {
+                constraints::check_difference(*a, *b, *diff, &self.grid)
+            }
+            Constraint::NegativeDifference(dotted, differences) => {
+                constraints::check_negative_difference(
+                    dotted,
+                    *differences,
+                    self.meta.size,
+                    &self.grid,
+                )
+            }
+            Constraint::Ratio(a, b, ratio) => constraints::check_ratio(*a, *b, *ratio, &self.grid),
+            Constraint::NegativeRatio(dotted, ratios) => {
+                constraints::check_negative_ratio(dotted, *ratios, self.meta.size, &self.grid)
+            }
+            Constraint::Sum(a, b, total) => constraints::check_sum(*a, *b, *total, &self.grid),
+            Constraint::NegativeSum(marked, totals) => {
+                constraints::check_negative_sum(marked, *totals, self.meta.size, &self.grid)
+            }
+            Constraint::Killer(cage, combinations) => {
+                constraints::check_killer(cage, combinations, &self.grid)
+            }
+            Constraint::Thermo(thermo) => constraints::check_thermo(thermo, &self.grid),
+            Constraint::Arrow(circle, shaft) => constraints::check_arrow(circle, shaft, &self.grid),
+            Constraint::Sandwich(line, total) => constraints::check_sandwich(
+                self.meta.line(*line),
+                *total,
+                self.meta.max_val,
+                &self.grid,
+            ),
+            Constraint::LittleKiller(diagonal, total) => {
+                constraints::check_little_killer(diagonal, *total, &self.grid)
+            }
+            Constraint::Whisper(line, n) => constraints::check_whisper(line, *n, &self.grid),
+            Constraint::Renban(line) => constraints::check_renban(line, &self.grid),
+            Constraint::Equal(pairs) => constraints::check_equal(pairs, &self.grid),
+            Constraint::Between(line) => constraints::check_between(line, &self.grid),
+            Constraint::Lockout(line, n) => constraints::check_lockout(line, *n, &self.grid),
+            Constraint::XSum(sightline, total) => {
+                constraints::check_xsum(&self.meta.sightline(*sightline), *total, &self.grid)
+            }
+            Constraint::Skyscraper(sightline, seen) => {
+                constraints::check_skyscraper(&self.meta.sightline(*sightline), *seen, &self.grid)
+            }
+            Constraint::Entropic(line) => constraints::check_classes(
+                line,
+                &constraints::entropic_classes(self.meta.max_val),
+                &self.grid,
+            ),
+            Constraint::Modular(line) => constraints::check_classes(
+                line,
+                &constraints::modular_classes(self.meta.max_val),
+                &self.grid,
+            ),
+            Constraint::RegionSum(line) => constraints::check_region_sum(
+                &constraints::region_segments(line, &self.meta.regions),
+                &self.grid,
+            ),
         }
     }
 
@@ -307,7 +385,85 @@ impl Board {
             Constraint::Quad(idx, single, double) => {
                 constraints::init_quad(*idx, *single, *double, self.meta.size, &mut self.grid)
             }
-            Constraint::Region(_) => Ok(Elimination::Same),
+            Constraint::Region(_)
+            | Constraint::AntiKnight
+            | Constraint::AntiKing
+            | Constraint::Nonconsecutive
+            | Constraint::NegativeDifference(_, _)
+            | Constraint::NegativeRatio(_, _)
+            | Constraint::NegativeSum(_, _) => Ok(Elimination::Same),
+            Constraint::Difference(a, b, diff) => {
+                constraints::difference_enforce_consistency(*a, *b, *diff, &mut self.grid)
+            }
+            Constraint::Ratio(a, b, ratio) => {
+                constraints::ratio_enforce_consistency(*a, *b, *ratio, &mut self.grid)
+            }
+            Constraint::Sum(a, b, total) => {
+                constraints::sum_enforce_consistency(*a, *b, *total, &mut self.grid)
+            }
+            Constraint::Killer(cage, combinations) => {
+                constraints::killer_enforce_consistency(cage, combinations, &mut self.grid)
+            }
+            Constraint::Thermo(thermo) => {
+                constraints::thermo_enforce_consistency(thermo, &mut self.grid)
+            }
+            Constraint::Arrow(circle, shaft) => {
+                constraints::arrow_enforce_consistency(circle, shaft, &mut self.grid)
+            }
+            Constraint::Sandwich(line, total) => {
+                let meta = self.meta.clone();
+                constraints::sandwich_enforce_consistency(
+                    meta.line(*line),
+                    *total,
+                    meta.max_val,
+                    &mut self.grid,
+                )
+            }
+            Constraint::LittleKiller(diagonal, total) => {
+                constraints::little_killer_enforce_consistency(diagonal, *total, &mut self.grid)
+            }
+            Constraint::Whisper(line, n) => {
+                constraints::whisper_enforce_consistency(line, *n, &mut self.grid)
+            }
+            Constraint::Renban(line) => {
+                constraints::renban_enforce_consistency(line, self.meta.max_val, &mut self.grid)
+            }
+            Constraint::Equal(pairs) => {
+                constraints::equal_enforce_consistency(pairs, &mut self.grid)
+            }
+            Constraint::Between(line) => {
+                constraints::between_enforce_consistency(line, &mut self.grid)
+            }
+            Constraint::Lockout(line, n) => {
+                constraints::lockout_enforce_consistency(line, *n, &mut self.grid)
+            }
+            Constraint::XSum(sightline, total) => constraints::xsum_enforce_consistency(
+                &self.meta.sightline(*sightline),
+                *total,
+                self.meta.max_val,
+                &mut self.grid,
+            ),
+            Constraint::Skyscraper(sightline, seen) => constraints::skyscraper_enforce_consistency(
+                &self.meta.sightline(*sightline),
+                *seen,
+                self.meta.max_val,
+                &mut self.grid,
+            ),
+            Constraint::Entropic(line) => constraints::classes_enforce_consistency(
+                line,
+                &constraints::entropic_classes(self.meta.max_val),
+                &mut self.grid,
+            ),
+            Constraint::Modular(line) => constraints::classes_enforce_consistency(
+                line,
+                &constraints::modular_classes(self.meta.max_val),
+                &mut self.grid,
+            ),
+            Constraint::RegionSum(line) => constraints::region_sum_enforce_consistency(
+                &constraints::region_segments(line, &self.meta.regions),
+                self.meta.max_val,
+                &mut self.grid,
+            ),
         }
     }
 
@@ -329,15 +485,243 @@ impl Board {
             Constraint::Region(region) => {
                 constraints::region_enforce_consistency(idx, value, region, &mut self.grid)
             }
+            Constraint::AntiKnight => constraints::offsets_enforce_consistency(
+                idx,
+                value,
+                &constraints::KNIGHT_OFFSETS,
+                self.meta.size,
+                &mut self.grid,
+            ),
+            Constraint::AntiKing => constraints::offsets_enforce_consistency(
+                idx,
+                value,
+                &constraints::KING_OFFSETS,
+                self.meta.size,
+                &mut self.grid,
+            ),
+            Constraint::Nonconsecutive => constraints::nonconsecutive_enforce_consistency(
+                idx,
+                value,
+                self.meta.size,
+                &mut self.grid,
+            ),
+            Constraint::Difference(a, b, diff) => {
+                constraints::difference_enforce_consistency(*a, *b, *diff, &mut self.grid)
+            }
+            Constraint::NegativeDifference(dotted, differences) => {
+                constraints::negative_difference_enforce_consistency(
+                    idx,
+                    value,
+                    dotted,
+                    *differences,
+                    self.meta.size,
+                    &mut self.grid,
+                )
+            }
+            Constraint::Ratio(a, b, ratio) => {
+                constraints::ratio_enforce_consistency(*a, *b, *ratio, &mut self.grid)
+            }
+            Constraint::Sum(a, b, total) => {
+                constraints::sum_enforce_consistency(*a, *b, *total, &mut self.grid)
+            }
+            Constraint::NegativeRatio(dotted, ratios) => {
+                constraints::negative_ratio_enforce_consistency(
+                    idx,
+                    value,
+                    dotted,
+                    *ratios,
+                    self.meta.size,
+                    &mut self.grid,
+                )
+            }
+            Constraint::NegativeSum(marked, totals) => {
+                constraints::negative_sum_enforce_consistency(
+                    idx,
+                    value,
+                    marked,
+                    *totals,
+                    self.meta.size,
+                    &mut self.grid,
+                )
+            }
+            Constraint::Killer(cage, combinations) => {
+                if cage.contains(&idx) {
+                    constraints::killer_enforce_consistency(cage, combinations, &mut self.grid)
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::Thermo(thermo) => {
+                if thermo.contains(&idx) {
+                    constraints::thermo_enforce_consistency(thermo, &mut self.grid)
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::Arrow(circle, shaft) => {
+                if circle.contains(&idx) || shaft.contains(&idx) {
+                    constraints::arrow_enforce_consistency(circle, shaft, &mut self.grid)
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::Sandwich(line, total) => {
+                let meta = self.meta.clone();
+                let cells = meta.line(*line);
+                if cells.contains(&idx) {
+                    constraints::sandwich_enforce_consistency(
+                        cells,
+                        *total,
+                        meta.max_val,
+                        &mut self.grid,
+                    )
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::LittleKiller(diagonal, total) => {
+                if diagonal.contains(&idx) {
+                    constraints::little_killer_enforce_consistency(diagonal, *total, &mut self.grid)
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::Whisper(line, n) => {
+                if line.contains(&idx) {
+                    constraints::whisper_enforce_consistency(line, *n, &mut self.grid)
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::Renban(line) => {
+                if line.contains(&idx) {
+                    constraints::renban_enforce_consistency(line, self.meta.max_val, &mut self.grid)
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::Equal(pairs) => {
+                let mut ret = constraints::equal_enforce_consistency(pairs, &mut self.grid)?;
+                // Copy the placement over to the other cell of its pair.
+                for (a, b) in pairs {
+                    let other = if *a == idx {
+                        *b
+                    } else if *b == idx {
+                        *a
+                    } else {
+                        continue;
+                    };
+                    if !self.solved_digits[other] {
+                        ret &= self.assign(other, value)?;
+                    }
+                }
+
+                Ok(ret)
+            }
+            Constraint::Between(line) => {
+                if line.contains(&idx) {
+                    constraints::between_enforce_consistency(line, &mut self.grid)
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::Lockout(line, n) => {
+                if line.contains(&idx) {
+                    constraints::lockout_enforce_consistency(line, *n, &mut self.grid)
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::XSum(sightline, total) => {
+                if self.meta.line(sightline.line).contains(&idx) {
+                    constraints::xsum_enforce_consistency(
+                        &self.meta.sightline(*sightline),
+                        *total,
+                        self.meta.max_val,
+                        &mut self.grid,
+                    )
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::Entropic(line) => {
+                if line.contains(&idx) {
+                    constraints::classes_enforce_consistency(
+                        line,
+                        &constraints::entropic_classes(self.meta.max_val),
+                        &mut self.grid,
+                    )
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::Modular(line) => {
+                if line.contains(&idx) {
+                    constraints::classes_enforce_consistency(
+                        line,
+                        &constraints::modular_classes(self.meta.max_val),
+                        &mut self.grid,
+                    )
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::RegionSum(line) => {
+                if line.contains(&idx) {
+                    constraints::region_sum_enforce_consistency(
+                        &constraints::region_segments(line, &self.meta.regions),
+                        self.meta.max_val,
+                        &mut self.grid,
+                    )
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
+            Constraint::Skyscraper(sightline, seen) => {
+                if self.meta.line(sightline.line).contains(&idx) {
+                    constraints::skyscraper_enforce_consistency(
+                        &self.meta.sightline(*sightline),
+                        *seen,
+                        self.meta.max_val,
+                        &mut self.grid,
+                    )
+                } else {
+                    Ok(Elimination::Same)
+                }
+            }
         }
     }
 
-    fn get_additional_region_from_constraint<'a>(
+    pub(crate) fn get_additional_region_from_constraint<'a>(
         meta: &'a Arc<BoardMeta>,
         c: &'a Constraint,
     ) -> Option<&'a [usize]> {
         match c {
-            Constraint::Quad(_, _, _) => None,
+            Constraint::Quad(_, _, _)
+            | Constraint::AntiKnight
+            | Constraint::AntiKing
+            | Constraint::Nonconsecutive
+            | Constraint::Difference(_, _, _)
+            | Constraint::NegativeDifference(_, _)
+            | Constraint::Ratio(_, _, _)
+            | Constraint::NegativeRatio(_, _)
+            | Constraint::Sum(_, _, _)
+            | Constraint::NegativeSum(_, _)
+            | Constraint::Killer(_, _)
+            | Constraint::Thermo(_)
+            | Constraint::Arrow(_, _)
+            | Constraint::Sandwich(_, _)
+            | Constraint::LittleKiller(_, _)
+            | Constraint::Whisper(_, _)
+            | Constraint::Renban(_)
+            | Constraint::Equal(_)
+            | Constraint::Between(_)
+            | Constraint::Lockout(_, _)
+            | Constraint::XSum(_, _)
+            | Constraint::Skyscraper(_, _)
+            | Constraint::Entropic(_)
+            | Constraint::Modular(_)
+            | Constraint::RegionSum(_) => None,
             Constraint::Region(region) => {
                 if region.len() == meta.size {
                     Some(region)
@@ -437,6 +821,116 @@ impl Board {
         Ok(ret)
     }
 
+    /// Every row, column and region of the grid, including extra regions from constraints.
+    pub(crate) fn units(meta: &Arc<BoardMeta>) -> Vec<&[usize]> {
+        meta.rows
+            .iter()
+            .chain(&meta.columns)
+            .chain(&meta.regions)
+            .map(Vec::as_slice)
+            .chain(
+                meta.constraints
+                    .iter()
+                    .filter_map(|c| Self::get_additional_region_from_constraint(meta, c)),
+            )
+            .collect()
+    }
+
+    /// Units that only share a single cell can't tell us anything a hidden single won't, so only
+    /// pairs that share more are kept for locked candidates.
+    fn overlapping_units(meta: &Arc<BoardMeta>) -> Vec<Overlap> {
+        let units = Self::units(meta);
+        let mut ret = Vec::new();
+        for (u, unit) in units.iter().enumerate() {
+            for (o, other) in units.iter().enumerate() {
+                let (shared, rest): (Vec<usize>, Vec<usize>) =
+                    unit.iter().partition(|idx| other.contains(idx));
+                if unit != other && shared.len() > 1 {
+                    let other_rest = other.iter().filter(|idx| !unit.contains(idx)).copied();
+                    ret.push(Overlap {
+                        unit: u,
+                        other: o,
+                        shared,
+                        rest,
+                        other_rest: other_rest.collect(),
+                    });
+                }
+            }
+        }
+        ret
+    }
+
+    /// Each of `Board::units` as a set of cells.
+    fn unit_cells(meta: &Arc<BoardMeta>) -> Vec<MoreBits> {
+        Self::units(meta)
+            .iter()
+            .map(|unit| {
+                let mut bits = MoreBits::ZERO;
+                for idx in *unit {
+                    bits.set(*idx, true);
+                }
+                bits
+            })
+            .collect()
+    }
+
+    /// The cells that share a unit with each cell, including the cell itself.
+    fn peers(meta: &Arc<BoardMeta>, unit_cells: &[MoreBits]) -> Vec<MoreBits> {
+        (0..meta.size * meta.size)
+            .map(|idx| {
+                let mut bits = MoreBits::ZERO;
+                for cells in unit_cells.iter().filter(|cells| cells[idx]) {
+                    bits |= *cells;
+                }
+                bits
+            })
+            .collect()
+    }
+
+    /// If all of the candidates for a digit in one unit are in the cells it shares with another,
+    /// then the digit has to go in those cells. Remove it from the rest of the other unit.
+    fn locked_candidates_helper(
+        &mut self,
+        shared: &[usize],
+        rest: &[usize],
+        other_rest: &[usize],
+    ) -> Result<Elimination, Contradiction> {
+        let mut locked = 0;
+        for idx in shared {
+            if !self.solved_digits[*idx] {
+                locked |= self.grid[*idx];
+            }
+        }
+        for idx in rest {
+            locked &= !self.grid[*idx];
+        }
+        if locked == 0 {
+            return Ok(Elimination::Same);
+        }
+
+        let mut ret = Elimination::Same;
+        for idx in other_rest {
+            ret &= self.eliminate(*idx, locked)?;
+        }
+
+        Ok(ret)
+    }
+
+    /// Remove candidates using the overlap between pairs of units. When a digit in a region can
+    /// only go in one row, it can't go anywhere else in that row (pointing), and the other way
+    /// around (claiming). This works for any pair of rows, columns and regions that overlap.
+    ///
+    /// # Errors
+    /// This will throw an error if searching for locked candidates leads to a contradiction.
+    pub fn locked_candidates(&mut self) -> Result<Elimination, Contradiction> {
+        let mut ret = Elimination::Same;
+        let meta = self.meta.clone();
+        for o in &meta.overlapping_units {
+            ret &= self.locked_candidates_helper(&o.shared, &o.rest, &o.other_rest)?;
+        }
+        Ok(ret)
+    }
+
     fn naked_tuple_helper(
         &mut self,
         n: usize,
@@ -515,72 +1009,329 @@ impl Board {
         Ok(ret)
     }
 
-    /*
-    #[allow(dead_code)]
-    fn hidden_tuples_helper<T: Iterator<Item = usize>>(
+    fn hidden_tuples_helper(
         &mut self,
-        _n: usize,
-        _unit: &T,
+        n: usize,
+        unit: &[usize],
     ) -> Result<Elimination, Contradiction> {
-        let ret = Elimination::Same;
+        let mut used_digits = 0;
+        for i in unit {
+            if self.solved_digits[*i] {
+                used_digits |= self.grid[*i];
+            }
+        }
+
+        let mut ret = Elimination::Same;
+        for vs in (1..=self.meta.max_val)
+            .filter(|v| (1 << *v) & used_digits == 0)
+            .combinations(n)
+        {
+            let mut digits = 0;
+            for v in vs {
+                digits |= 1 << v;
+            }
+
+            let indices: Vec<usize> = unit
+                .iter()
+                .filter(|i| !self.solved_digits[**i] && self.grid[**i] & digits != 0)
+                .copied()
+                .collect();
+
+            // n digits can't fit in fewer than n cells.
+            if indices.len() < n {
+                return Err(Contradiction(()));
+            }
+            if indices.len() == n {
+                for i in indices {
+                    ret &= self.eliminate(i, !digits)?;
+                }
+            }
+        }
+
         Ok(ret)
     }
-    */
 
-    pub(crate) fn next_idx_to_guess(&self) -> Option<usize> {
-        let mut count = self.meta.size + 1;
+    /// Locate n values that can only go in n cells, and remove the other digits from those cells.
+    ///
+    /// # Errors
+    /// This will throw an error if searching for hidden tuples leads to a contradiction.
+    pub fn hidden_tuples(&mut self, n: usize) -> Result<Elimination, Contradiction> {
+        let mut ret = Elimination::Same;
+        let meta = self.meta.clone();
+        // rows
+        for r in &meta.rows {
+            ret &= self.hidden_tuples_helper(n, r)?;
+        }
+        // columns
+        for c in &meta.columns {
+            ret &= self.hidden_tuples_helper(n, c)?;
+        }
+        // regions
+        for reg in &meta.regions {
+            ret &= self.hidden_tuples_helper(n, reg)?;
+        }
+        // Additional regions from constraints.
+        for c in &meta.constraints {
+            if let Some(reg) = Self::get_additional_region_from_constraint(&meta, c) {
+                ret &= self.hidden_tuples_helper(n, reg)?;
+            }
+        }
+        Ok(ret)
+    }
+
+    /// The pairs of families of units that fish use as base and cover sets, as indices into
+    /// `Board::units`. Rows are covered by columns, and the other way around. Regions can be added
+    /// to both sides.
+    fn fish_families(&self) -> Vec<(Vec<usize>, Vec<usize>)> {
+        let size = self.meta.size;
+        let mut rows: Vec<usize> = (0..size).collect();
+        let mut columns: Vec<usize> = (size..2 * size).collect();
+        if self.meta.deductions.fish_regions {
+            let regions = 2 * size..2 * size + self.meta.regions.len();
+            rows.extend(regions.clone());
+            columns.extend(regions);
+        }
+        vec![(rows.clone(), columns.clone()), (columns, rows)]
+    }
+
+    /// Look for a fish of size `n` that removes at least one candidate.
+    ///
+    /// Each base unit has to hold the digit, so the `n` base units hold it `n` times. If all of
+    /// those cells are in `n` cover units, each cover holds one of them, and the digit can't go
+    /// anywhere else in the covers. Base cells that are outside of the covers are fins. If one of
+    /// the fins holds the digit, anything that sees it can't, so with fins the eliminations are
+    /// limited to cells that see all of them.
+    pub(crate) fn find_fish(&self, n: usize, finned: bool) -> Option<Fish> {
         let mut ret = None;
+        self.scan_fish(n, finned, |fish| {
+            ret = Some(fish);
+            false
+        });
+        ret
+    }
 
-        for (i, d) in self.grid.iter().enumerate() {
-            if d.count_ones() == 1 {
-                continue;
+    /// Pass each fish of size `n` that removes at least one candidate to `found`, until it returns
+    /// false.
+    fn scan_fish(&self, n: usize, finned: bool, mut found: impl FnMut(Fish) -> bool) {
+        let meta = self.meta.clone();
+        let unit_cells = &meta.unit_cells;
+        let families = self.fish_families();
+
+        for digit in 1..=meta.max_val {
+            let v = 1 << digit;
+            let mut candidates = MoreBits::ZERO;
+            let mut placed = MoreBits::ZERO;
+            for (idx, d) in self.grid.iter().enumerate() {
+                if d & v != 0 {
+                    if self.solved_digits[idx] {
+                        placed.set(idx, true);
+                    } else {
+                        candidates.set(idx, true);
+                    }
+                }
             }
-            if (d.count_ones() as usize) < count {
-                count = d.count_ones() as usize;
-                ret = Some(i);
+
+            for (base_family, cover_family) in &families {
+                let covers: Vec<MoreBits> = cover_family.iter().map(|c| unit_cells[*c]).collect();
+                // A unit where the digit is placed can't be a base.
+                let bases: Vec<FishBase> = base_family
+                    .iter()
+                    .filter(|u| (unit_cells[**u] & placed).not_any())
+                    .map(|u| {
+                        let cells = unit_cells[*u] & candidates;
+                        let covers = covers
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, c)| (**c & cells).any())
+                            .fold(0, |acc, (j, _)| acc | 1 << j);
+                        let itself = cover_family.iter().position(|c| c == u);
+                        FishBase {
+                            unit: *u,
+                            cells,
+                            covers,
+                            itself: itself.map_or(0, |j| 1 << j),
+                        }
+                    })
+                    .filter(|b| b.cells.any())
+                    .collect();
+
+                let search = FishSearch {
+                    n,
+                    finned,
+                    // Without regions, each base cell is in a single cover, so a basic fish can't
+                    // touch more than `n` of them.
+                    single_cover: !meta.deductions.fish_regions,
+                    digit,
+                    candidates,
+                    bases: &bases,
+                    covers: &covers,
+                    cover_family,
+                    meta: &meta,
+                };
+                let partial = PartialFish {
+                    units: MoreBits::ZERO,
+                    cells: MoreBits::ZERO,
+                    covers: 0,
+                    excluded: 0,
+                };
+                if !search.choose_bases(0, &mut Vec::new(), partial, &mut found) {
+                    return;
+                }
             }
         }
-        ret
     }
 
-    #[allow(dead_code)]
-    pub(crate) fn get_values(&self, idx: usize) -> Bits {
-        self.grid[idx]
+    /// Remove candidates with fish of size `n`, like X-Wings and Swordfish. Finned fish are only
+    /// looked for if `finned` is set. All of the fish are found in one pass over the board before
+    /// any candidates are removed, which is fine as removing candidates never breaks a fish.
+    ///
+    /// # Errors
+    /// This will throw an error if searching for fish leads to a contradiction.
+    pub fn fish(&mut self, n: usize, finned: bool) -> Result<Elimination, Contradiction> {
+        let mut fishes = Vec::new();
+        self.scan_fish(n, finned, |fish| {
+            fishes.push(fish);
+            true
+        });
+
+        let mut ret = Elimination::Same;
+        for fish in fishes {
+            for idx in fish.eliminations {
+                ret &= self.eliminate(idx, 1 << fish.digit)?;
+            }
+        }
+        Ok(ret)
     }
 
-    pub(crate) fn deduce(&mut self) -> Result<(), Contradiction> {
-        loop {
-            while self.naked_singles()? == Elimination::Eliminated {}
-            if self.hidden_singles()? == Elimination::Eliminated {
-                continue;
+    /// Look for tuples, smallest first, using the sizes allowed by the board's deductions. Stops
+    /// at the first size that makes progress.
+    fn tuples(&mut self) -> Result<Elimination, Contradiction> {
+        let deductions = self.meta.deductions;
+        for n in 2..=deductions.naked_tuples.max(deductions.hidden_tuples) {
+            if n <= deductions.naked_tuples && self.naked_tuples(n)? == Elimination::Eliminated {
+                return Ok(Elimination::Eliminated);
             }
-            if self.solved() {
-                break;
+            if n <= deductions.hidden_tuples && self.hidden_tuples(n)? == Elimination::Eliminated {
+                return Ok(Elimination::Eliminated);
             }
-            // if self.naked_tuples(2)? == Elimination::Eliminated {
-            //     continue;
-            // }
-            // if self.solved() {
-            //     break;
-            // }
-            // if self.naked_tuples(3)? == Elimination::Eliminated {
-            //     continue;
-            // }
-            // if self.solved() {
-            //     break;
-            // }
-            // if self.naked_tuples(4)? == Elimination::Eliminated {
-            //     continue;
-            // }
-            break;
         }
-        Ok(())
+        Ok(Elimination::Same)
     }
 
-    /// An iterator of all possible solutions to the given puzzle.
-    #[must_use]
-    pub fn solutions(&self) -> SolutionIterator<Board> {
-        SolutionIterator::new(self)
+    /// Look for basic fish, smallest first, up to the size allowed by the board's deductions. Stops
+    /// at the first size that makes progress. Finned fish are left to the step engine, as they
+    /// take far longer to find than guessing does.
+    fn fishes(&mut self) -> Result<Elimination, Contradiction> {
+        for n in 2..=self.meta.deductions.fish {
+            if self.fish(n, false)? == Elimination::Eliminated {
+                return Ok(Elimination::Eliminated);
+            }
+        }
+        Ok(Elimination::Same)
+    }
+
+    /// Choose which techniques are used to narrow down candidates between guesses.
+    pub fn set_deductions(&mut self, deductions: Deductions) {
+        Arc::make_mut(&mut self.meta).deductions = deductions;
+    }
+
+    /// Add a line that can't be imported from f-puzzles. `cells` are indices into the grid, in
+    /// order along the line. A lockout line with fewer than 2 cells has no ends, and entropic and
+    /// modular lines only say something about runs of 3 cells, so shorter lines are ignored.
+    ///
+    /// # Errors
+    /// This function can return an error if
+    /// - An index in `cells` is out of bounds for the grid.
+    /// - The line leaves a cell with no candidates.
+    pub fn add_line(&mut self, line: ExtraLine, cells: Vec<usize>) -> Result<(), SudokuErrors> {
+        if cells.iter().any(|idx| *idx >= self.len()) {
+            return Err(SudokuErrors::OutOfBounds);
+        }
+        let constraint = match line {
+            ExtraLine::Lockout(n) if cells.len() >= 2 => Constraint::Lockout(cells, n),
+            ExtraLine::Entropic if cells.len() >= 3 => Constraint::Entropic(cells),
+            ExtraLine::Modular if cells.len() >= 3 => Constraint::Modular(cells),
+            ExtraLine::RegionSum => Constraint::RegionSum(cells),
+            ExtraLine::Lockout(_) | ExtraLine::Entropic | ExtraLine::Modular => return Ok(()),
+        };
+        Arc::make_mut(&mut self.meta).constraints.push(constraint);
+        self.init_constraints()?;
+        Ok(())
+    }
+
+    pub(crate) fn next_idx_to_guess(&self) -> Option<usize> {
+        let mut count = self.meta.size + 1;
+        let mut ret = None;
+
+        for (i, d) in self.grid.iter().enumerate() {
+            if d.count_ones() == 1 {
+                continue;
+            }
+            if (d.count_ones() as usize) < count {
+                count = d.count_ones() as usize;
+                ret = Some(i);
+            }
+        }
+        ret
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn get_values(&self, idx: usize) -> Bits {
+        self.grid[idx]
+    }
+
+    /// The digits that are still candidates in cell `idx`.
+    #[must_use]
+    pub fn candidates(&self, idx: usize) -> Vec<usize> {
+        self.iter_ones(idx)
+    }
+
+    /// The digits placed in the grid, listed by index. Cells that have not been solved are
+    /// reported as 0.
+    #[must_use]
+    pub fn digits(&self) -> Vec<usize> {
+        self.grid
+            .iter()
+            .enumerate()
+            .map(|(i, d)| {
+                if self.solved_digits[i] {
+                    d.trailing_zeros() as usize
+                } else {
+                    0
+                }
+            })
+            .collect()
+    }
+
+    pub(crate) fn deduce(&mut self) -> Result<(), Contradiction> {
+        loop {
+            while self.naked_singles()? == Elimination::Eliminated {}
+            if self.hidden_singles()? == Elimination::Eliminated {
+                continue;
+            }
+            if self.solved() {
+                break;
+            }
+            if self.meta.deductions.locked_candidates
+                && self.locked_candidates()? == Elimination::Eliminated
+            {
+                continue;
+            }
+            if self.tuples()? == Elimination::Eliminated {
+                continue;
+            }
+            if self.fishes()? == Elimination::Eliminated {
+                continue;
+            }
+            break;
+        }
+        Ok(())
+    }
+
+    /// An iterator of all possible solutions to the given puzzle.
+    #[must_use]
+    pub fn solutions(&self) -> SolutionIterator<Board> {
+        SolutionIterator::new(self)
     }
 
     fn solution_count_helper(&mut self, token: &CancellationToken, tx: &Sender<usize>) -> usize {
@@ -594,8 +1345,8 @@ impl Board {
             return 1;
         }
         let Some(idx) = self.next_idx_to_guess() else {
-                return 0;
-            };
+            return 0;
+        };
         let count = self
             .iter_ones(idx)
             .par_iter()
@@ -662,6 +1413,224 @@ impl Board {
     }
 }
 
+/// A unit that could be a base of a fish.
+struct FishBase {
+    unit: usize,
+
+    /// The cells of the unit that can hold the digit.
+    cells: MoreBits,
+
+    /// A mask of the covers those cells are in.
+    covers: usize,
+
+    /// The unit itself, if it is also in the cover family.
+    itself: usize,
+}
+
+/// The base units chosen so far for a fish.
+#[derive(Clone, Copy)]
+struct PartialFish {
+    units: MoreBits,
+    cells: MoreBits,
+    covers: usize,
+    excluded: usize,
+}
+
+/// Looks for fish on one digit, with one family of units as bases and another as covers.
+struct FishSearch<'a> {
+    n: usize,
+    finned: bool,
+    single_cover: bool,
+    digit: usize,
+    candidates: MoreBits,
+    bases: &'a [FishBase],
+    covers: &'a [MoreBits],
+    cover_family: &'a [usize],
+    meta: &'a BoardMeta,
+}
+
+impl FishSearch<'_> {
+    /// Add bases from `start` on to those already `chosen`, and pass each fish they make to
+    /// `found`. Returns false once `found` does.
+    fn choose_bases(
+        &self,
+        start: usize,
+        chosen: &mut Vec<usize>,
+        partial: PartialFish,
+        found: &mut impl FnMut(Fish) -> bool,
+    ) -> bool {
+        let touched = (partial.covers & !partial.excluded).count_ones() as usize;
+        if !self.finned && self.single_cover && touched > self.n {
+            return true;
+        }
+        if chosen.len() == self.n {
+            return touched < self.n || self.choose_covers(chosen, partial, found);
+        }
+
+        for i in start..self.bases.len() {
+            let base = &self.bases[i];
+            let unit = self.meta.unit_cells[base.unit];
+            // Base units that overlap could share the digit.
+            if (partial.units & unit).any() {
+                continue;
+            }
+
+            chosen.push(i);
+            let next = PartialFish {
+                units: partial.units | unit,
+                cells: partial.cells | base.cells,
+                covers: partial.covers | base.covers,
+                excluded: partial.excluded | base.itself,
+            };
+            if !self.choose_bases(i + 1, chosen, next, found) {
+                return false;
+            }
+            chosen.pop();
+        }
+        true
+    }
+
+    /// Find covers for the bases that are `chosen`, and pass the fish to `found`.
+    fn choose_covers(
+        &self,
+        chosen: &[usize],
+        partial: PartialFish,
+        found: &mut impl FnMut(Fish) -> bool,
+    ) -> bool {
+        let allowed = partial.covers & !partial.excluded;
+        let mut covers = Vec::new();
+        let Some(eliminations) = self.cover(
+            allowed,
+            partial.cells,
+            MoreBits::ZERO,
+            self.candidates & !partial.units,
+            &mut covers,
+        ) else {
+            return true;
+        };
+
+        covers.sort_unstable();
+        let mut kept = MoreBits::ZERO;
+        for j in &covers {
+            kept |= self.covers[*j];
+        }
+        found(Fish {
+            digit: self.digit,
+            bases: chosen.iter().map(|i| self.bases[*i].unit).collect(),
+            covers: covers.iter().map(|j| self.cover_family[*j]).collect(),
+            fins: (partial.cells & !kept).iter_ones().collect(),
+            eliminations: eliminations.iter_ones().collect(),
+        })
+    }
+
+    /// Choose `allowed` covers for the `uncovered` base cells, one cell at a time, on top of those
+    /// already `chosen`. A cell can be covered by any cover it is in, or, for finned fish, be left
+    /// as a fin. The digit can only be removed from `possible` cells that see all of the fins and
+    /// are in a chosen cover. Returns those cells, with the covers in `chosen`.
+    fn cover(
+        &self,
+        allowed: usize,
+        uncovered: MoreBits,
+        kept: MoreBits,
+        possible: MoreBits,
+        chosen: &mut Vec<usize>,
+    ) -> Option<MoreBits> {
+        let Some(cell) = uncovered.first_one() else {
+            let eliminations = possible & kept;
+            return (chosen.len() == self.n && eliminations.any()).then_some(eliminations);
+        };
+
+        if chosen.len() < self.n {
+            for (j, cells) in self.covers.iter().enumerate() {
+                if allowed & 1 << j == 0 || !cells[cell] || chosen.contains(&j) {
+                    continue;
+                }
+                chosen.push(j);
+                if let Some(eliminations) = self.cover(
+                    allowed,
+                    uncovered & !*cells,
+                    kept | *cells,
+                    possible,
+                    chosen,
+                ) {
+                    return Some(eliminations);
+                }
+                chosen.pop();
+            }
+        }
+
+        if self.finned {
+            let possible = possible & self.meta.peers[cell];
+            if possible.any() {
+                let mut uncovered = uncovered;
+                uncovered.set(cell, false);
+                return self.cover(allowed, uncovered, kept, possible, chosen);
+            }
+        }
+        None
+    }
+}
+
+/// A fish on a single digit, found by `Board::find_fish`. Units are indexed in the order of
+/// `Board::units`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Fish {
+    pub(crate) digit: usize,
+    pub(crate) bases: Vec<usize>,
+    pub(crate) covers: Vec<usize>,
+    pub(crate) fins: Vec<usize>,
+    pub(crate) eliminations: Vec<usize>,
+}
+
+/// The kinds of line that can be drawn with an f-puzzles line object.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LineKind {
+    /// Adjacent digits differ by at least the given amount.
+    Whisper(usize),
+    /// The digits are a set of consecutive digits, in any order.
+    Renban,
+}
+
+/// Outline colours of f-puzzles line objects, and the kind of line they are read as. A line with a
+/// colour that isn't listed is treated as decoration.
+const LINE_COLOURS: [(&str, LineKind); 3] = [
+    ("#67F067", LineKind::Whisper(5)),
+    ("#FFA500", LineKind::Whisper(4)),
+    ("#F067F0", LineKind::Renban),
+];
+
+/// Work out the constraints for one line of an f-puzzles line object. The value of a line replaces
+/// the default amount for kinds of line that take one, so a whisper line with a value is a
+/// difference line, where adjacent digits differ by at least the value. A line with a colour that
+/// isn't listed is decoration, even if it has a value.
+fn line_constraints(line: &CustomLine, cells: Vec<usize>) -> Result<Vec<Constraint>, SudokuErrors> {
+    // A line with no cells has nothing to constrain.
+    if cells.is_empty() {
+        return Ok(Vec::new());
+    }
+    let value = match &line.value {
+        Some(v) => Some(v.parse().map_err(|_| SudokuErrors::BadDigit)?),
+        None => None,
+    };
+    let kind = LINE_COLOURS
+        .iter()
+        .find(|(c, _)| c.eq_ignore_ascii_case(&line.outline_c))
+        .map(|(_, kind)| *kind);
+    let kind = match (kind, value) {
+        (Some(LineKind::Whisper(_)), Some(n)) => Some(LineKind::Whisper(n)),
+        (kind, _) => kind,
+    };
+
+    Ok(match kind {
+        Some(LineKind::Whisper(n)) => vec![Constraint::Whisper(cells, n)],
+        // Digits can't repeat on a renban line.
+        Some(LineKind::Renban) => {
+            vec![Constraint::Renban(cells.clone()), Constraint::Region(cells)]
+        }
+        None => Vec::new(),
+    })
+}
+
 fn regions(f: &FPuzzles) -> Vec<Vec<usize>> {
     let (width, height) = DIMENSIONS[f.size - 1];
     let mut ret = vec![Vec::new(); f.size];
@@ -683,7 +1652,23 @@ fn regions(f: &FPuzzles) -> Vec<Vec<usize>> {
     ret
 }
 
-pub(crate) fn rc_to_idx(s: &str, size: usize) -> Result<usize, SudokuErrors> {
+/// Build the disjoint groups for a set of regions. Each region lists its cells in reading order, so
+/// the nth group is made up of the nth cell of every region. This also works for irregular
+/// regions.
+fn disjoint_groups(regions: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut ret = vec![Vec::with_capacity(regions.len()); regions.len()];
+    for region in regions {
+        for (i, idx) in region.iter().enumerate() {
+            ret[i].push(*idx);
+        }
+    }
+
+    ret
+}
+
+/// Split an RC notated cell into its row and column numbers, without checking that they are in the
+/// grid.
+fn parse_rc(s: &str) -> Result<(usize, usize), SudokuErrors> {
     let Some(offset) = s.find('C') else {
         return Err(SudokuErrors::BadRCEncoding);
     };
@@ -700,16 +1685,94 @@ pub(crate) fn rc_to_idx(s: &str, size: usize) -> Result<usize, SudokuErrors> {
     let Ok(y) = row.parse::<usize>() else {
         return Err(SudokuErrors::BadRCEncoding);
     };
-    if x > size {
+
+    Ok((y, x))
+}
+
+pub(crate) fn rc_to_idx(s: &str, size: usize) -> Result<usize, SudokuErrors> {
+    let (y, x) = parse_rc(s)?;
+    if x == 0 || x > size {
         return Err(SudokuErrors::BadRCEncoding);
     };
-    if y > size {
+    if y == 0 || y > size {
         return Err(SudokuErrors::BadRCEncoding);
     };
 
     Ok((y - 1) * size + (x - 1))
 }
 
+/// Find the row or column that a clue outside the grid refers to.
+fn outside_line(s: &str, size: usize) -> Result<Line, SudokuErrors> {
+    let (y, x) = parse_rc(s)?;
+    let outside = |n| n == 0 || n == size + 1;
+    if outside(y) && (1..=size).contains(&x) {
+        Ok(Line::Column(x - 1))
+    } else if outside(x) && (1..=size).contains(&y) {
+        Ok(Line::Row(y - 1))
+    } else {
+        Err(SudokuErrors::BadRCEncoding)
+    }
+}
+
+/// The row or column of an outside clue, read starting next to the clue.
+fn sightline(s: &str, size: usize) -> Result<Sightline, SudokuErrors> {
+    let line = outside_line(s, size)?;
+    let (y, x) = parse_rc(s)?;
+    Ok(Sightline {
+        line,
+        reversed: y == size + 1 || x == size + 1,
+    })
+}
+
+/// The cells along the diagonal of a little killer clue, starting next to the clue.
+fn diagonal(s: &str, direction: Diagonal, size: usize) -> Result<Vec<usize>, SudokuErrors> {
+    let (y, x) = parse_rc(s)?;
+    let inside = |n| (1..=size).contains(&n);
+    if inside(y) && inside(x) {
+        return Err(SudokuErrors::BadRCEncoding);
+    }
+    let (dr, dc) = match direction {
+        Diagonal::UpRight => (-1, 1),
+        Diagonal::UpLeft => (-1, -1),
+        Diagonal::DownRight => (1, 1),
+        Diagonal::DownLeft => (1, -1),
+    };
+
+    let mut ret = Vec::new();
+    let (mut r, mut c) = (y, x);
+    while let (Some(next_r), Some(next_c)) = (r.checked_add_signed(dr), c.checked_add_signed(dc)) {
+        if !inside(next_r) || !inside(next_c) {
+            break;
+        }
+        (r, c) = (next_r, next_c);
+        ret.push((r - 1) * size + (c - 1));
+    }
+    if ret.is_empty() {
+        return Err(SudokuErrors::BadRCEncoding);
+    }
+
+    Ok(ret)
+}
+
+fn cell_pair(cells: &[String; 2], size: usize) -> Result<(usize, usize), SudokuErrors> {
+    Ok((rc_to_idx(&cells[0], size)?, rc_to_idx(&cells[1], size)?))
+}
+
+fn cell_list(cells: &[String], size: usize) -> Result<Vec<usize>, SudokuErrors> {
+    cells.iter().map(|rc| rc_to_idx(rc, size)).collect()
+}
+
+fn parse_clue(pair: &CellPair, default: usize) -> Result<usize, SudokuErrors> {
+    match &pair.value {
+        Some(v) => v.parse().map_err(|_| SudokuErrors::BadDigit),
+        None => Ok(default),
+    }
+}
+
+pub(crate) fn idx_to_rc(idx: usize, size: usize) -> String {
+    format!("R{}C{}", idx / size + 1, idx % size + 1)
+}
+
 impl TryFrom<&FPuzzles> for Board {
     type Error = SudokuErrors;
 
@@ -759,20 +1822,266 @@ impl TryFrom<&FPuzzles> for Board {
             constraints.push(Constraint::Region(region));
         }
 
-        let mut ret = if f.is_irregular() {
+        if f.antiknight {
+            constraints.push(Constraint::AntiKnight);
+        }
+
+        if f.antiking {
+            constraints.push(Constraint::AntiKing);
+        }
+
+        let mut white_dotted = Vec::with_capacity(f.difference.len());
+        let mut differences = 0;
+        for d in &f.difference {
+            let (a, b) = cell_pair(&d.cells, f.size)?;
+            let diff = parse_clue(d, 1)?;
+            if diff >= f.size {
+                return Err(SudokuErrors::ValueTooLarge);
+            }
+            if diff == 0 {
+                return Err(SudokuErrors::Contradiction);
+            }
+            white_dotted.push((a.min(b), a.max(b)));
+            differences |= to_bits(diff);
+            constraints.push(Constraint::Difference(a, b, diff));
+        }
+
+        let mut dotted = white_dotted.clone();
+        let mut ratios = 0;
+        for r in &f.ratio {
+            let (a, b) = cell_pair(&r.cells, f.size)?;
+            let ratio = parse_clue(r, 2)?;
+            if ratio > f.size {
+                return Err(SudokuErrors::ValueTooLarge);
+            }
+            if ratio < 2 {
+                return Err(SudokuErrors::Contradiction);
+            }
+            dotted.push((a.min(b), a.max(b)));
+            ratios |= to_bits(ratio);
+            constraints.push(Constraint::Ratio(a, b, ratio));
+        }
+
+        // Pairs of cells with either kind of dot are exempt from both negative constraints. That
+        // way, a black dot between a 1 and a 2 doesn't also need a white dot.
+        if f.negative.contains(&Negative::Ratio) {
+            constraints.push(Constraint::NegativeRatio(
+                dotted.clone(),
+                ratios | to_bits(2),
+            ));
+        }
+
+        if f.negative.contains(&Negative::Difference) {
+            constraints.push(Constraint::NegativeDifference(
+                dotted,
+                differences | to_bits(1),
+            ));
+        }
+
+        // Nonconsecutive is a global rule, so only a white dot can allow consecutive digits. A
+        // black dot between a 1 and a 2 would still break it.
+        if f.nonconsecutive {
+            if white_dotted.is_empty() {
+                constraints.push(Constraint::Nonconsecutive);
+            } else {
+                constraints.push(Constraint::NegativeDifference(white_dotted, to_bits(1)));
+            }
+        }
+
+        let mut marked = Vec::with_capacity(f.xv.len());
+        for c in &f.xv {
+            let (a, b) = cell_pair(&c.cells, f.size)?;
+            let total = match c.value {
+                Xv::X => 10,
+                Xv::V => 5,
+            };
+            marked.push((a.min(b), a.max(b)));
+            constraints.push(Constraint::Sum(a, b, total));
+        }
+        if f.negative.contains(&Negative::Xv) {
+            constraints.push(Constraint::NegativeSum(marked, to_bits(5) | to_bits(10)));
+        }
+
+        // A cage is a region that isn't allowed to repeat digits, with a sum on top.
+        for cage in &f.killercage {
+            let cells = cell_list(&cage.cells, f.size)?;
+            if let Some(v) = &cage.value {
+                let total = v.parse().map_err(|_| SudokuErrors::BadDigit)?;
+                let combinations = constraints::cage_combinations(cells.len(), total, f.size);
+                constraints.push(Constraint::Killer(cells.clone(), combinations));
+            }
+            constraints.push(Constraint::Region(cells));
+        }
+
+        for t in &f.thermometer {
+            for line in &t.lines {
+                constraints.push(Constraint::Thermo(cell_list(line, f.size)?));
+            }
+        }
+
+        // Every line of an arrow starts in the circle, so the shaft is the rest of the cells.
+        for a in &f.arrow {
+            let circle = cell_list(&a.cells, f.size)?;
+            let mut shaft = Vec::new();
+            for line in &a.lines {
+                for idx in cell_list(line, f.size)? {
+                    if !circle.contains(&idx) && !shaft.contains(&idx) {
+                        shaft.push(idx);
+                    }
+                }
+            }
+            constraints.push(Constraint::Arrow(circle, shaft));
+        }
+
+        for clue in &f.sandwichsum {
+            let line = outside_line(&clue.cell, f.size)?;
+            if let Some(v) = &clue.value {
+                let total = v.parse().map_err(|_| SudokuErrors::BadDigit)?;
+                constraints.push(Constraint::Sandwich(line, total));
+            }
+        }
+
+        for clue in &f.xsum {
+            let sightline = sightline(&clue.cell, f.size)?;
+            if let Some(v) = &clue.value {
+                let total = v.parse().map_err(|_| SudokuErrors::BadDigit)?;
+                constraints.push(Constraint::XSum(sightline, total));
+            }
+        }
+
+        for clue in &f.skyscraper {
+            let sightline = sightline(&clue.cell, f.size)?;
+            if let Some(v) = &clue.value {
+                let seen = v.parse().map_err(|_| SudokuErrors::BadDigit)?;
+                if seen > f.size {
+                    return Err(SudokuErrors::ValueTooLarge);
+                }
+                if seen == 0 {
+                    return Err(SudokuErrors::Contradiction);
+                }
+                constraints.push(Constraint::Skyscraper(sightline, seen));
+            }
+        }
+
+        for clue in &f.littlekillersum {
+            let cells = diagonal(&clue.cell, clue.direction, f.size)?;
+            if let Some(v) = &clue.value {
+                let total = v.parse().map_err(|_| SudokuErrors::BadDigit)?;
+                constraints.push(Constraint::LittleKiller(cells, total));
+            }
+        }
+
+        for p in &f.palindrome {
+            for line in &p.lines {
+                let cells = cell_list(line, f.size)?;
+                let pairs = cells
+                    .iter()
+                    .zip(cells.iter().rev())
+                    .take(cells.len() / 2)
+                    .map(|(a, b)| (*a, *b))
+                    .collect();
+                constraints.push(Constraint::Equal(pairs));
+            }
+        }
+
+        // The cells of a clone are paired up in the order they are listed.
+        for c in &f.clone {
+            let cells = cell_list(&c.cells, f.size)?;
+            let clones = cell_list(&c.clone_cells, f.size)?;
+            if cells.len() != clones.len() {
+                return Err(SudokuErrors::CloneWrongSizes);
+            }
+            let pairs = cells
+                .into_iter()
+                .zip(clones)
+                .filter(|(a, b)| a != b)
+                .collect();
+            constraints.push(Constraint::Equal(pairs));
+        }
+
+        for b in &f.betweenline {
+            for line in &b.lines {
+                let cells = cell_list(line, f.size)?;
+                // A line with nothing between the circles doesn't constrain them.
+                if cells.len() > 2 {
+                    constraints.push(Constraint::Between(cells));
+                }
+            }
+        }
+
+        for l in &f.line {
+            for cells in &l.lines {
+                constraints.extend(line_constraints(l, cell_list(cells, f.size)?)?);
+            }
+        }
+
+        // A minimum or maximum is a short thermometer to each of its orthogonal neighbours.
+        for m in &f.minimum {
+            let idx = rc_to_idx(&m.cell, f.size)?;
+            for n in constraints::offset_neighbors(idx, f.size, &constraints::ORTHOGONAL_OFFSETS) {
+                constraints.push(Constraint::Thermo(vec![idx, n]));
+            }
+        }
+        for m in &f.maximum {
+            let idx = rc_to_idx(&m.cell, f.size)?;
+            for n in constraints::offset_neighbors(idx, f.size, &constraints::ORTHOGONAL_OFFSETS) {
+                constraints.push(Constraint::Thermo(vec![n, idx]));
+            }
+        }
+
+        let reg = if f.is_irregular() {
             let reg = regions(f);
             if reg.iter().any(|x| x.len() != f.size) {
                 return Err(SudokuErrors::IrregularWrongSizes);
             }
-            Board::new_with_regions(f.size, f.size, reg, constraints)?
+            reg
         } else {
-            Board::new_with_constraints(f.size, f.size, constraints)?
+            build_default_regions(f.size)?
         };
 
+        if f.disjointgroups {
+            for group in disjoint_groups(&reg) {
+                constraints.push(Constraint::Region(group));
+            }
+        }
+
+        let mut ret = Board::new_with_regions(f.size, f.size, reg, constraints)?;
+
+        let mut deductions = Deductions::default();
+        if f.disabledlogic.contains(&Logic::Pointing) {
+            deductions.locked_candidates = false;
+        }
+        if f.disabledlogic.contains(&Logic::Tuples) {
+            deductions.naked_tuples = 0;
+            deductions.hidden_tuples = 0;
+        }
+        if f.disabledlogic.contains(&Logic::Fishes) {
+            deductions.fish = 0;
+            deductions.fish_steps = false;
+        }
+        ret.set_deductions(deductions);
+
+        let odds = (1..=f.size).step_by(2).fold(0, |acc, v| acc | to_bits(v));
+        let evens = (2..=f.size).step_by(2).fold(0, |acc, v| acc | to_bits(v));
+        for m in &f.odd {
+            ret.eliminate(rc_to_idx(&m.cell, f.size)?, evens)?;
+        }
+        for m in &f.even {
+            ret.eliminate(rc_to_idx(&m.cell, f.size)?, odds)?;
+        }
+        // Let the other constraints see the parity of the marked cells.
+        if !f.odd.is_empty() || !f.even.is_empty() {
+            ret.init_constraints()?;
+        }
+
         for (r, row) in f.grid.iter().enumerate() {
             for (c, cell) in row.iter().enumerate() {
                 if let Some(v) = cell.value {
-                    ret.assign(r * f.size + c, to_bits(v as usize))?;
+                    let bits = to_bits(v as usize);
+                    if !ret.possible_value(r * f.size + c, bits) {
+                        return Err(SudokuErrors::Contradiction);
+                    }
+                    ret.assign(r * f.size + c, bits)?;
                 } else if !cell.given_pencil_marks.is_empty() {
                     for v in 1..=ret.meta.max_val {
                         if !cell.given_pencil_marks.contains(&v) {
@@ -825,6 +2134,9 @@ impl Solvable for Board {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use f_puzzles::{
+        Arrow, Cage, CloneRegion, Line as FLine, LittleKiller, Marker, OutsideClue, XvClue,
+    };
 
     use tokio::sync::mpsc::channel;
     use tokio_util::sync::CancellationToken;
@@ -1253,6 +2565,14 @@ mod tests {
         assert_eq!(rc_to_idx("", 9), Err(SudokuErrors::BadRCEncoding));
     }
 
+    #[test]
+    fn test_idx_to_rc() {
+        assert_eq!(idx_to_rc(0, 9), "R1C1");
+        assert_eq!(idx_to_rc(24, 9), "R3C7");
+        assert_eq!(idx_to_rc(255, 16), "R16C16");
+        assert_eq!(rc_to_idx(&idx_to_rc(42, 9), 9), Ok(42));
+    }
+
     #[test]
     fn too_many_digits() {
         let mut board = Board::new(6, 9).unwrap();
@@ -1288,6 +2608,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn digits() {
+        let mut board = Board::new(4, 4).unwrap();
+        assert_eq!(board.assign(0, ONE), Ok(Elimination::Eliminated));
+        assert_eq!(board.assign(5, THREE), Ok(Elimination::Eliminated));
+        let digits = board.digits();
+        assert_eq!(digits.len(), 16);
+        assert_eq!(digits[0], 1);
+        assert_eq!(digits[5], 3);
+        assert_eq!(digits[1], 0);
+    }
+
     #[test]
     fn solution_count() {
         let b = from_string(
@@ -1382,16 +2714,189 @@ mod tests {
     }
 
     #[test]
-    fn windoku() {
-        let mut f = FPuzzles::new(9);
-        f.extraregion.push(f_puzzles::Region {
-            cells: vec![
-                "R2C2".to_string(),
-                "R2C3".to_string(),
-                "R2C4".to_string(),
-                "R3C2".to_string(),
-                "R3C3".to_string(),
-                "R3C4".to_string(),
+    fn locked_candidates() {
+        let mut board = Board::new(9, 9).unwrap();
+        assert_eq!(board.locked_candidates(), Ok(Elimination::Same));
+
+        // Pointing: the 1 in box 1 has to go in row 1, so it can't go anywhere else in the row.
+        // Claiming: the 2 in row 1 has to go in box 1, so it can't go anywhere else in the box.
+        for idx in [9, 10, 11, 18, 19, 20] {
+            board.eliminate(idx, ONE).unwrap();
+        }
+        for idx in 3..9 {
+            board.eliminate(idx, TWO).unwrap();
+        }
+        assert_eq!(board.locked_candidates(), Ok(Elimination::Eliminated));
+        for idx in 3..9 {
+            assert_eq!(board.grid[idx] & ONE, 0);
+        }
+        for idx in [9, 10, 11, 18, 19, 20] {
+            assert_eq!(board.grid[idx] & TWO, 0);
+        }
+        assert_ne!(board.grid[12] & TWO, 0);
+        assert_ne!(board.grid[27] & ONE, 0);
+    }
+
+    #[test]
+    fn hidden_tuples() {
+        // 1 and 2 are both kept out of the rest of row 1, so they must go in R1C1 and R1C2.
+        let mut board = from_string(
+            "............1...2......21.............1........2.................................",
+        )
+        .unwrap();
+        assert_eq!(board.hidden_tuples(2), Ok(Elimination::Eliminated));
+        assert_eq!(board.grid[0], ONE | TWO);
+        assert_eq!(board.grid[1], ONE | TWO);
+        assert_eq!(board.hidden_tuples(2), Ok(Elimination::Same));
+
+        let mut board = Board::new(9, 9).unwrap();
+        for idx in 1..9 {
+            board.eliminate(idx, ONE | TWO).unwrap();
+        }
+        assert!(board.hidden_tuples(2).is_err());
+    }
+
+    #[test]
+    fn x_wing() {
+        // The 1 in rows 1 and 5 can only go in columns 1 and 5.
+        let mut board = Board::new(9, 9).unwrap();
+        assert_eq!(board.fish(2, true), Ok(Elimination::Same));
+        for idx in [1, 2, 3, 5, 6, 7, 8, 37, 38, 39, 41, 42, 43, 44] {
+            board.eliminate(idx, ONE).unwrap();
+        }
+        assert_eq!(
+            board.find_fish(2, false),
+            Some(Fish {
+                digit: 1,
+                bases: vec![0, 4],
+                covers: vec![9, 13],
+                fins: vec![],
+                eliminations: vec![9, 13, 18, 22, 27, 31, 45, 49, 54, 58, 63, 67, 72, 76],
+            })
+        );
+        assert_eq!(board.fish(2, false), Ok(Elimination::Eliminated));
+        for r in [1, 2, 3, 5, 6, 7, 8] {
+            assert_eq!(board.grid[r * 9] & ONE, 0);
+            assert_eq!(board.grid[r * 9 + 4] & ONE, 0);
+            assert_ne!(board.grid[r * 9 + 1] & ONE, 0);
+        }
+        assert_eq!(board.fish(2, false), Ok(Elimination::Same));
+    }
+
+    #[test]
+    fn finned_x_wing() {
+        // As above, but with a fin at R5C6. Either the fin is a 1, or the X-Wing is there, so
+        // only cells in box 5 that are in a cover lose the 1.
+        let mut board = Board::new(9, 9).unwrap();
+        for idx in [1, 2, 3, 5, 6, 7, 8, 37, 38, 39, 42, 43, 44] {
+            board.eliminate(idx, ONE).unwrap();
+        }
+        assert_eq!(board.find_fish(2, false), None);
+        assert_eq!(
+            board.find_fish(2, true),
+            Some(Fish {
+                digit: 1,
+                bases: vec![0, 4],
+                covers: vec![9, 13],
+                fins: vec![41],
+                eliminations: vec![31, 49],
+            })
+        );
+        assert_eq!(board.fish(2, true), Ok(Elimination::Eliminated));
+        assert_eq!(board.grid[31] & ONE, 0);
+        assert_eq!(board.grid[49] & ONE, 0);
+        assert_ne!(board.grid[9] & ONE, 0);
+    }
+
+    #[test]
+    fn region_fish() {
+        // The 1 in row 1 can only go in R1C1, R1C2 or R1C5, and in row 2 in R2C2, R2C3 or R2C5.
+        // That is box 1 and column 5 between them, so neither can have a 1 anywhere else. It is
+        // also boxes 1 and 2, so they can't have a 1 in row 3.
+        let mut board = Board::new(9, 9).unwrap();
+        for idx in [2, 3, 5, 6, 7, 8, 9, 12, 14, 15, 16, 17] {
+            board.eliminate(idx, ONE).unwrap();
+        }
+        assert_eq!(board.find_fish(2, false), None);
+
+        board.set_deductions(Deductions {
+            fish_regions: true,
+            ..Deductions::default()
+        });
+        assert_eq!(
+            board.find_fish(2, false),
+            Some(Fish {
+                digit: 1,
+                bases: vec![0, 1],
+                covers: vec![13, 18],
+                fins: vec![],
+                eliminations: vec![18, 19, 20, 22, 31, 40, 49, 58, 67, 76],
+            })
+        );
+        assert_eq!(board.fish(2, false), Ok(Elimination::Eliminated));
+        for idx in [18, 19, 20, 22, 31, 76] {
+            assert_eq!(board.grid[idx] & ONE, 0);
+        }
+        assert_eq!(board.fish(2, false), Ok(Elimination::Eliminated));
+        assert_eq!(board.grid[21] & ONE, 0);
+        assert_eq!(board.grid[23] & ONE, 0);
+    }
+
+    #[test]
+    fn deductions_from_disabled_logic() {
+        let mut f = FPuzzles::new(9);
+        let b = Board::try_from(&f).unwrap();
+        assert_eq!(b.meta.deductions, Deductions::default());
+
+        f.disabledlogic = vec![Logic::Pointing, Logic::Tuples, Logic::Fishes];
+        let b = Board::try_from(&f).unwrap();
+        assert!(!b.meta.deductions.locked_candidates);
+        assert_eq!(b.meta.deductions.naked_tuples, 0);
+        assert_eq!(b.meta.deductions.hidden_tuples, 0);
+        assert_eq!(b.meta.deductions.fish, 0);
+        assert!(!b.meta.deductions.fish_steps);
+    }
+
+    #[test]
+    fn deduce_with_tuples() {
+        let repr =
+            "85...24..72......9..4.........1.7..23.5...9...4...........8..7..17..........36.4.";
+        let mut board = from_string(repr).unwrap();
+        board.set_deductions(Deductions {
+            locked_candidates: true,
+            naked_tuples: 4,
+            hidden_tuples: 4,
+            ..Deductions::default()
+        });
+        let mut plain = from_string(repr).unwrap();
+        plain.set_deductions(Deductions {
+            locked_candidates: false,
+            naked_tuples: 0,
+            hidden_tuples: 0,
+            ..Deductions::default()
+        });
+        assert!(board.deduce().is_ok());
+        assert!(plain.deduce().is_ok());
+        for idx in 0..81 {
+            assert_eq!(board.grid[idx] & plain.grid[idx], board.grid[idx]);
+        }
+        assert_eq!(
+            crate::solve(&board).unwrap().digits(),
+            crate::solve(&plain).unwrap().digits()
+        );
+    }
+
+    #[test]
+    fn windoku() {
+        let mut f = FPuzzles::new(9);
+        f.extraregion.push(f_puzzles::Region {
+            cells: vec![
+                "R2C2".to_string(),
+                "R2C3".to_string(),
+                "R2C4".to_string(),
+                "R3C2".to_string(),
+                "R3C3".to_string(),
+                "R3C4".to_string(),
                 "R4C2".to_string(),
                 "R4C3".to_string(),
                 "R4C4".to_string(),
@@ -1486,4 +2991,980 @@ mod tests {
         let mut b = res_b.unwrap();
         assert_eq!(b.solution_count_max(100), 1);
     }
+
+    #[test]
+    fn antiknight() {
+        let mut f = FPuzzles::new(4);
+        f.antiknight = true;
+        let res_b = Board::try_from(&f);
+        assert!(res_b.is_ok());
+        let b = res_b.unwrap();
+        assert_eq!(b.meta.constraints, vec![Constraint::AntiKnight]);
+
+        // Of the 288 4x4 sudoku grids, exactly 24 have no repeated digits a knight's move apart.
+        let mut count = 0;
+        for sln in b.solutions() {
+            for idx in 0..16 {
+                for i in constraints::offset_neighbors(idx, 4, &constraints::KNIGHT_OFFSETS) {
+                    assert_ne!(sln.grid[idx], sln.grid[i]);
+                }
+            }
+            count += 1;
+        }
+        assert_eq!(count, 24);
+    }
+
+    #[test]
+    fn antiknight_solved() {
+        let mut f = FPuzzles::new(4);
+        f.antiknight = true;
+        let mut b = Board::try_from(&f).unwrap();
+        // A valid sudoku, but the 1s in R1C1 and R2C3 are a knight's move apart.
+        for (i, v) in [1, 2, 3, 4, 3, 4, 1, 2, 2, 1, 4, 3, 4, 3, 2, 1]
+            .iter()
+            .enumerate()
+        {
+            b.grid[i] = to_bits(*v);
+            b.solved_digits.set(i, true);
+        }
+        assert!(!b.solved());
+    }
+
+    #[test]
+    fn antiking() {
+        let mut f = FPuzzles::new(6);
+        f.antiking = true;
+        let res_b = Board::try_from(&f);
+        assert!(res_b.is_ok());
+        let mut b = res_b.unwrap();
+        assert_eq!(b.meta.constraints, vec![Constraint::AntiKing]);
+
+        assert!(b.assign(14, THREE).is_ok());
+        for i in [7, 9, 19, 21] {
+            assert!(!b.possible_value(i, THREE));
+        }
+        assert!(b.possible_value(22, THREE));
+
+        // Every 2x2 box in a 4x4 grid forces a diagonal repeat with its neighbours.
+        let mut f = FPuzzles::new(4);
+        f.antiking = true;
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.solution_count_max(10), 0);
+    }
+
+    #[test]
+    fn antiking_solved() {
+        let mut f = FPuzzles::new(4);
+        f.antiking = true;
+        let mut b = Board::try_from(&f).unwrap();
+        for (i, v) in [1, 2, 3, 4, 3, 4, 1, 2, 2, 1, 4, 3, 4, 3, 2, 1]
+            .iter()
+            .enumerate()
+        {
+            b.grid[i] = to_bits(*v);
+            b.solved_digits.set(i, true);
+        }
+        assert!(!b.solved());
+    }
+
+    #[test]
+    fn test_disjoint_groups() {
+        let groups = disjoint_groups(&build_default_regions(9).unwrap());
+        assert_eq!(groups.len(), 9);
+        assert_eq!(groups[0], vec![0, 3, 6, 27, 30, 33, 54, 57, 60]);
+        assert_eq!(groups[8], vec![20, 23, 26, 47, 50, 53, 74, 77, 80]);
+
+        let mut f = FPuzzles::new(9);
+        f.grid[0][3].region = Some(0);
+        f.grid[2][2].region = Some(1);
+        let groups = disjoint_groups(&regions(&f));
+        assert_eq!(groups[3][0], 3);
+        assert_eq!(groups[3][1], 13);
+    }
+
+    #[test]
+    fn disjoint_groups_from_f_puzzles() {
+        let mut f = FPuzzles::new(4);
+        f.disjointgroups = true;
+        let res_b = Board::try_from(&f);
+        assert!(res_b.is_ok());
+        let mut b = res_b.unwrap();
+        assert_eq!(b.meta.constraints.len(), 4);
+        assert_eq!(b.meta.constraints[0], Constraint::Region(vec![0, 2, 8, 10]));
+
+        // Of the 288 4x4 sudoku grids, 168 have each position within a box filled with different
+        // digits.
+        assert_eq!(b.solution_count_max(1000), 168);
+
+        assert!(b.assign(0, ONE).is_ok());
+        for i in [2, 8, 10] {
+            assert!(!b.possible_value(i, ONE));
+        }
+    }
+
+    #[test]
+    fn disjoint_groups_irregular() {
+        let mut f = FPuzzles::new(4);
+        f.disjointgroups = true;
+        f.grid[0][2].region = Some(0);
+        f.grid[1][1].region = Some(1);
+        let mut b = Board::try_from(&f).unwrap();
+        // Region 0 is R1C1, R1C2, R1C3, R2C1, so its second cell is R1C2.
+        // Region 1 is R1C4, R2C2, R2C3, R2C4, so its second cell is R2C2.
+        assert_eq!(b.meta.constraints[1], Constraint::Region(vec![1, 5, 9, 11]));
+        assert!(b.assign(1, TWO).is_ok());
+        assert!(!b.possible_value(5, TWO));
+        assert!(b.hidden_singles().is_ok());
+    }
+
+    #[test]
+    fn nonconsecutive() {
+        let mut f = FPuzzles::new(9);
+        f.nonconsecutive = true;
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.meta.constraints, vec![Constraint::Nonconsecutive]);
+        assert!(b.assign(40, FIVE).is_ok());
+        for i in [31, 39, 41, 49] {
+            assert!(!b.possible_value(i, FOUR));
+            assert!(!b.possible_value(i, SIX));
+        }
+        assert!(b.possible_value(30, FOUR));
+    }
+
+    #[test]
+    fn miracle() {
+        const SOLUTION: [usize; 81] = [
+            4, 8, 3, 7, 2, 6, 1, 5, 9, 7, 2, 6, 1, 5, 9, 4, 8, 3, 1, 5, 9, 4, 8, 3, 7, 2, 6, 8, 3,
+            7, 2, 6, 1, 5, 9, 4, 2, 6, 1, 5, 9, 4, 8, 3, 7, 5, 9, 4, 8, 3, 7, 2, 6, 1, 3, 7, 2, 6,
+            1, 5, 9, 4, 8, 6, 1, 5, 9, 4, 8, 3, 7, 2, 9, 4, 8, 3, 7, 2, 6, 1, 5,
+        ];
+        let mut f = FPuzzles::new(9);
+        f.antiknight = true;
+        f.antiking = true;
+        f.nonconsecutive = true;
+        f.grid[4][2].value = Some(1);
+        f.grid[5][6].value = Some(2);
+
+        let mut b = Board::try_from(&f).unwrap();
+        let sln = crate::solve(&b);
+        assert!(sln.is_ok());
+        assert_eq!(sln.unwrap().digits(), SOLUTION);
+        assert_eq!(b.solution_count_max(10), 1);
+    }
+
+    fn white_dots(f: &mut FPuzzles) {
+        for cells in [
+            ["R1C2", "R1C3"],
+            ["R1C2", "R2C2"],
+            ["R1C3", "R2C3"],
+            ["R2C2", "R2C3"],
+            ["R3C1", "R4C1"],
+            ["R3C4", "R4C4"],
+        ] {
+            f.difference.push(CellPair {
+                cells: cells.map(str::to_string),
+                value: None,
+            });
+        }
+    }
+
+    #[test]
+    fn difference() {
+        let mut f = FPuzzles::new(4);
+        white_dots(&mut f);
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.meta.constraints[0], Constraint::Difference(1, 2, 1));
+        assert_eq!(b.solution_count_max(1000), 64);
+
+        f.difference[0].value = Some("2".to_string());
+        let b = Board::try_from(&f).unwrap();
+        assert_eq!(b.grid[1], ONE | TWO | THREE | FOUR);
+        f.difference[0].value = Some("3".to_string());
+        let b = Board::try_from(&f).unwrap();
+        assert_eq!(b.grid[1], ONE | FOUR);
+
+        f.difference[0].value = Some("4".to_string());
+        assert_eq!(
+            Board::try_from(&f).unwrap_err(),
+            SudokuErrors::ValueTooLarge
+        );
+        f.difference[0].value = Some("0".to_string());
+        assert_eq!(
+            Board::try_from(&f).unwrap_err(),
+            SudokuErrors::Contradiction
+        );
+        f.difference[0].value = Some("one".to_string());
+        assert_eq!(Board::try_from(&f).unwrap_err(), SudokuErrors::BadDigit);
+    }
+
+    #[test]
+    fn negative_difference() {
+        let mut f = FPuzzles::new(4);
+        white_dots(&mut f);
+        f.negative.push(Negative::Difference);
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.solution_count_max(1000), 2);
+
+        // Nonconsecutive with white dots means the same thing.
+        f.negative.clear();
+        f.nonconsecutive = true;
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(
+            b.meta.constraints.last(),
+            Some(&Constraint::NegativeDifference(
+                vec![(1, 2), (1, 5), (2, 6), (5, 6), (8, 12), (11, 15)],
+                ONE
+            ))
+        );
+        assert_eq!(b.solution_count_max(1000), 2);
+    }
+
+    #[test]
+    fn ratio() {
+        let mut f = FPuzzles::new(9);
+        f.ratio.push(CellPair {
+            cells: ["R1C1".to_string(), "R1C2".to_string()],
+            value: None,
+        });
+        f.ratio.push(CellPair {
+            cells: ["R2C1".to_string(), "R2C2".to_string()],
+            value: Some("3".to_string()),
+        });
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.meta.constraints[0], Constraint::Ratio(0, 1, 2));
+        assert_eq!(b.meta.constraints[1], Constraint::Ratio(9, 10, 3));
+        assert_eq!(b.grid[0], ONE | TWO | THREE | FOUR | SIX | EIGHT);
+        assert_eq!(b.grid[9], ONE | TWO | THREE | SIX | NINE);
+
+        // Pruned from the bitmasks alone, without any guessing.
+        assert!(b.assign(1, EIGHT).is_ok());
+        assert_eq!(b.grid[0], FOUR);
+
+        f.ratio[0].value = Some("1".to_string());
+        assert_eq!(
+            Board::try_from(&f).unwrap_err(),
+            SudokuErrors::Contradiction
+        );
+        f.ratio[0].value = Some("10".to_string());
+        assert_eq!(
+            Board::try_from(&f).unwrap_err(),
+            SudokuErrors::ValueTooLarge
+        );
+    }
+
+    fn black_dots(f: &mut FPuzzles) {
+        for cells in [
+            ["R1C1", "R1C2"],
+            ["R2C3", "R2C4"],
+            ["R2C3", "R3C3"],
+            ["R2C4", "R3C4"],
+            ["R3C3", "R3C4"],
+            ["R4C1", "R4C2"],
+        ] {
+            f.ratio.push(CellPair {
+                cells: cells.map(str::to_string),
+                value: None,
+            });
+        }
+    }
+
+    #[test]
+    fn negative_ratio() {
+        let mut f = FPuzzles::new(4);
+        black_dots(&mut f);
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.solution_count_max(1000), 40);
+
+        f.negative.push(Negative::Ratio);
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(
+            b.meta.constraints[6],
+            Constraint::NegativeRatio(
+                vec![(0, 1), (6, 7), (6, 10), (7, 11), (10, 11), (12, 13)],
+                TWO
+            )
+        );
+        assert_eq!(b.solution_count_max(1000), 8);
+
+        // A black dot is enough to exempt a 1-2 pair from the negative white dot rule.
+        f.negative = vec![Negative::Difference];
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(
+            b.meta.constraints[6],
+            Constraint::NegativeDifference(
+                vec![(0, 1), (6, 7), (6, 10), (7, 11), (10, 11), (12, 13)],
+                ONE
+            )
+        );
+        assert!(b.assign(0, ONE).is_ok());
+        assert_eq!(b.grid[1], TWO);
+    }
+
+    #[test]
+    fn ratio_and_nonconsecutive() {
+        // Unlike a white dot, a black dot doesn't allow consecutive digits.
+        let mut f = FPuzzles::new(9);
+        f.ratio.push(CellPair {
+            cells: ["R1C1".to_string(), "R1C2".to_string()],
+            value: None,
+        });
+        f.nonconsecutive = true;
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(
+            b.meta.constraints,
+            vec![Constraint::Ratio(0, 1, 2), Constraint::Nonconsecutive]
+        );
+        assert!(b.assign(0, ONE).is_err());
+
+        // A white dot elsewhere still leaves the black dotted pair covered.
+        f.difference.push(CellPair {
+            cells: ["R9C8".to_string(), "R9C9".to_string()],
+            value: None,
+        });
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(
+            b.meta.constraints.last(),
+            Some(&Constraint::NegativeDifference(vec![(79, 80)], ONE))
+        );
+        assert!(b.clone().assign(0, ONE).is_err());
+        assert!(b.assign(79, ONE).is_ok());
+        assert!(b.possible_value(80, TWO));
+    }
+
+    #[test]
+    fn xv() {
+        let mut f = FPuzzles::new(9);
+        f.xv.push(XvClue {
+            cells: ["R1C1".to_string(), "R1C2".to_string()],
+            value: Xv::X,
+        });
+        f.xv.push(XvClue {
+            cells: ["R2C1".to_string(), "R2C2".to_string()],
+            value: Xv::V,
+        });
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.meta.constraints[0], Constraint::Sum(0, 1, 10));
+        assert_eq!(b.meta.constraints[1], Constraint::Sum(9, 10, 5));
+        assert_eq!(
+            b.grid[0],
+            ONE | TWO | THREE | FOUR | SIX | SEVEN | EIGHT | NINE
+        );
+        assert_eq!(b.grid[9], ONE | TWO | THREE | FOUR);
+
+        assert!(b.assign(1, THREE).is_ok());
+        assert_eq!(b.grid[0], SEVEN);
+    }
+
+    #[test]
+    fn negative_xv() {
+        let mut f = FPuzzles::new(4);
+        for r in 1..=4 {
+            f.xv.push(XvClue {
+                cells: [format!("R{r}C2"), format!("R{r}C3")],
+                value: Xv::V,
+            });
+        }
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.solution_count_max(1000), 48);
+
+        f.negative.push(Negative::Xv);
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(
+            b.meta.constraints[4],
+            Constraint::NegativeSum(
+                vec![(1, 2), (5, 6), (9, 10), (13, 14)],
+                (1 << 5) | (1 << 10)
+            )
+        );
+        assert_eq!(b.solution_count_max(1000), 8);
+
+        f.xv.clear();
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.solution_count_max(1000), 8);
+    }
+
+    #[test]
+    fn killer_cage() {
+        let mut f = FPuzzles::new(9);
+        f.killercage.push(Cage {
+            cells: vec!["R1C1".to_string(), "R1C2".to_string()],
+            value: Some("3".to_string()),
+        });
+        f.killercage.push(Cage {
+            cells: vec!["R2C1".to_string(), "R2C2".to_string()],
+            value: None,
+        });
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(
+            b.meta.constraints[0],
+            Constraint::Killer(vec![0, 1], vec![ONE | TWO])
+        );
+        assert_eq!(b.meta.constraints[1], Constraint::Region(vec![0, 1]));
+        assert_eq!(b.meta.constraints[2], Constraint::Region(vec![9, 10]));
+        assert_eq!(b.grid[0], ONE | TWO);
+        // A cage without a total only stops digits from repeating.
+        assert_eq!(b.grid[9].count_ones(), 9);
+
+        assert!(b.assign(0, TWO).is_ok());
+        assert_eq!(b.grid[1], ONE);
+
+        f.killercage[0].value = Some("18".to_string());
+        assert_eq!(
+            Board::try_from(&f).unwrap_err(),
+            SudokuErrors::Contradiction
+        );
+        f.killercage[0].value = Some("x".to_string());
+        assert_eq!(Board::try_from(&f).unwrap_err(), SudokuErrors::BadDigit);
+    }
+
+    #[test]
+    fn killer() {
+        const SOLUTION: &str =
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+        let mut f = FPuzzles::new(9);
+        for (cells, value) in [
+            ("R1C1 R1C2", 8),
+            ("R1C3 R1C4", 10),
+            ("R1C5 R2C5 R2C6", 21),
+            ("R1C6 R1C7 R1C8", 18),
+            ("R1C9 R2C9 R3C9", 17),
+            ("R2C1 R2C2 R3C2", 22),
+            ("R2C3 R3C3 R4C3", 19),
+            ("R2C4 R3C4", 4),
+            ("R2C7 R2C8", 7),
+            ("R3C1 R4C1", 9),
+            ("R3C5 R4C5", 10),
+            ("R3C6 R4C6", 3),
+            ("R3C7 R3C8", 11),
+            ("R4C2 R5C2 R5C3", 13),
+            ("R4C4 R5C4", 15),
+            ("R4C7 R5C7", 11),
+            ("R4C8 R4C9", 5),
+            ("R5C1 R6C1 R6C2", 12),
+            ("R5C5 R5C6", 8),
+            ("R5C8 R6C8", 14),
+            ("R5C9 R6C9", 7),
+            ("R6C3 R7C3", 4),
+            ("R6C4 R6C5 R6C6", 15),
+            ("R6C7 R7C7", 10),
+            ("R7C1 R7C2 R8C2", 23),
+            ("R7C4 R7C5", 8),
+            ("R7C6 R8C6 R8C7", 22),
+            ("R7C8 R8C8 R8C9", 16),
+            ("R7C9", 4),
+            ("R8C1 R9C1 R9C2", 9),
+            ("R8C3 R8C4 R9C3", 16),
+            ("R8C5 R9C5", 9),
+            ("R9C4", 2),
+            ("R9C6 R9C7", 7),
+            ("R9C8 R9C9", 16),
+        ] {
+            f.killercage.push(Cage {
+                cells: cells.split_whitespace().map(str::to_string).collect(),
+                value: Some(value.to_string()),
+            });
+        }
+
+        let mut b = Board::try_from(&f).unwrap();
+        let sln = crate::solve(&b).unwrap();
+        let digits: String = sln.digits().iter().map(ToString::to_string).collect();
+        assert_eq!(digits, SOLUTION);
+        assert_eq!(b.solution_count_max(10), 1);
+    }
+
+    #[test]
+    fn thermometer() {
+        let mut f = FPuzzles::new(9);
+        f.thermometer.push(FLine {
+            lines: vec![(1..=9).map(|c| format!("R1C{c}")).collect()],
+        });
+        f.thermometer.push(FLine {
+            lines: vec![vec![
+                "R2C1".to_string(),
+                "R3C1".to_string(),
+                "R4C1".to_string(),
+            ]],
+        });
+        let b = Board::try_from(&f).unwrap();
+        assert_eq!(b.meta.constraints[0], Constraint::Thermo((0..9).collect()));
+        for i in 0..9 {
+            assert_eq!(b.grid[i], 1 << (i + 1));
+        }
+        assert_eq!(b.grid[9], ONE | TWO | THREE | FOUR | FIVE | SIX | SEVEN);
+        assert_eq!(b.grid[18], TWO | THREE | FOUR | FIVE | SIX | SEVEN | EIGHT);
+        assert_eq!(b.grid[27], THREE | FOUR | FIVE | SIX | SEVEN | EIGHT | NINE);
+    }
+
+    #[test]
+    fn thermometer_branches() {
+        let mut f = FPuzzles::new(4);
+        f.thermometer.push(FLine {
+            lines: vec![
+                vec!["R1C1".to_string(), "R1C2".to_string(), "R2C2".to_string()],
+                vec!["R1C1".to_string(), "R2C1".to_string()],
+            ],
+        });
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.meta.constraints.len(), 2);
+        assert_eq!(b.solution_count_max(1000), 36);
+    }
+
+    #[test]
+    fn arrow() {
+        let mut f = FPuzzles::new(9);
+        f.arrow.push(Arrow {
+            lines: vec![vec![
+                "R1C2".to_string(),
+                "R2C3".to_string(),
+                "R3C3".to_string(),
+                "R4C3".to_string(),
+            ]],
+            cells: vec!["R1C1".to_string(), "R1C2".to_string()],
+        });
+        let b = Board::try_from(&f).unwrap();
+        assert_eq!(
+            b.meta.constraints[0],
+            Constraint::Arrow(vec![0, 1], vec![11, 20, 29])
+        );
+        // The shaft adds up to at most 27, so the circle can't start with a 3 or more.
+        assert_eq!(b.grid[0], ONE | TWO);
+    }
+
+    #[test]
+    fn arrow_solutions() {
+        let mut f = FPuzzles::new(4);
+        f.arrow.push(Arrow {
+            lines: vec![vec![
+                "R1C1".to_string(),
+                "R2C2".to_string(),
+                "R3C3".to_string(),
+            ]],
+            cells: vec!["R1C1".to_string()],
+        });
+        let mut b = Board::try_from(&f).unwrap();
+        // The shaft cells don't see each other, so 2 = 1 + 1 is still possible.
+        assert_eq!(b.grid[0], TWO | THREE | FOUR);
+        assert_eq!(b.solution_count_max(1000), 36);
+    }
+
+    #[test]
+    fn test_outside_line() {
+        assert_eq!(outside_line("R0C1", 9), Ok(Line::Column(0)));
+        assert_eq!(outside_line("R10C9", 9), Ok(Line::Column(8)));
+        assert_eq!(outside_line("R3C0", 9), Ok(Line::Row(2)));
+        assert_eq!(outside_line("R3C10", 9), Ok(Line::Row(2)));
+        assert_eq!(outside_line("R0C0", 9), Err(SudokuErrors::BadRCEncoding));
+        assert_eq!(outside_line("R3C3", 9), Err(SudokuErrors::BadRCEncoding));
+        assert_eq!(rc_to_idx("R0C1", 9), Err(SudokuErrors::BadRCEncoding));
+    }
+
+    #[test]
+    fn sandwich() {
+        let mut f = FPuzzles::new(4);
+        for (cell, value) in [("R1C0", "5"), ("R0C2", "0"), ("R3C5", "2")] {
+            f.sandwichsum.push(OutsideClue {
+                cell: cell.to_string(),
+                value: Some(value.to_string()),
+            });
+        }
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.meta.constraints[0], Constraint::Sandwich(Line::Row(0), 5));
+        assert_eq!(
+            b.meta.constraints[1],
+            Constraint::Sandwich(Line::Column(1), 0)
+        );
+        // 2 + 3 is the only way to make 5, so the 1 and 4 are at the ends of the row.
+        assert_eq!(b.grid[1], TWO | THREE);
+        assert_eq!(b.grid[2], TWO | THREE);
+        assert_eq!(b.solution_count_max(1000), 2);
+    }
+
+    #[test]
+    fn test_diagonal() {
+        assert_eq!(
+            diagonal("R0C0", Diagonal::DownRight, 4),
+            Ok(vec![0, 5, 10, 15])
+        );
+        assert_eq!(diagonal("R0C2", Diagonal::DownRight, 4), Ok(vec![2, 7]));
+        assert_eq!(
+            diagonal("R5C0", Diagonal::UpRight, 4),
+            Ok(vec![12, 9, 6, 3])
+        );
+        assert_eq!(diagonal("R2C5", Diagonal::DownLeft, 4), Ok(vec![11, 14]));
+        assert_eq!(
+            diagonal("R3C0", Diagonal::UpLeft, 4),
+            Err(SudokuErrors::BadRCEncoding)
+        );
+        assert_eq!(
+            diagonal("R1C1", Diagonal::UpLeft, 4),
+            Err(SudokuErrors::BadRCEncoding)
+        );
+    }
+
+    #[test]
+    fn little_killer() {
+        let mut f = FPuzzles::new(9);
+        f.littlekillersum.push(LittleKiller {
+            cell: "R0C5".to_string(),
+            direction: Diagonal::DownRight,
+            value: Some("6".to_string()),
+        });
+        let b = Board::try_from(&f).unwrap();
+        assert_eq!(
+            b.meta.constraints[0],
+            Constraint::LittleKiller(vec![5, 15, 25, 35], 6)
+        );
+        // Four cells add up to 6, so none of them can be more than 3.
+        for idx in [5, 15, 25, 35] {
+            assert_eq!(b.grid[idx], ONE | TWO | THREE);
+        }
+    }
+
+    fn custom_line(cells: &[&str], colour: &str, value: Option<&str>) -> CustomLine {
+        CustomLine {
+            lines: vec![cells.iter().map(ToString::to_string).collect()],
+            outline_c: colour.to_string(),
+            value: value.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn whispers() {
+        let mut f = FPuzzles::new(9);
+        f.line
+            .push(custom_line(&["R1C1", "R1C2", "R1C3"], "#67f067", None));
+        f.line.push(custom_line(&["R2C1", "R2C2"], "#FFA500", None));
+        f.line
+            .push(custom_line(&["R3C1", "R3C2"], "#67F067", Some("7")));
+        f.line.push(custom_line(&["R4C1", "R4C2"], "#000000", None));
+        f.line
+            .push(custom_line(&["R5C1", "R5C2"], "#000000", Some("7")));
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.meta.constraints.len(), 3);
+        assert_eq!(b.meta.constraints[0], Constraint::Whisper(vec![0, 1, 2], 5));
+        assert_eq!(b.meta.constraints[1], Constraint::Whisper(vec![9, 10], 4));
+        assert_eq!(b.meta.constraints[2], Constraint::Whisper(vec![18, 19], 7));
+
+        // 5 has no partner on a German whisper, but it can sit next to a 1 or a 9 on a Dutch
+        // whisper. Nothing from 3 to 7 has a partner on a difference line of 7.
+        assert_eq!(
+            b.grid[1],
+            ONE | TWO | THREE | FOUR | SIX | SEVEN | EIGHT | NINE
+        );
+        assert_eq!(b.grid[9].count_ones(), 9);
+        assert_eq!(b.grid[18], ONE | TWO | EIGHT | NINE);
+
+        assert!(b.assign(1, FOUR).is_ok());
+        assert_eq!(b.grid[0], NINE);
+        assert_eq!(b.grid[2], NINE);
+    }
+
+    #[test]
+    fn renban() {
+        let mut f = FPuzzles::new(9);
+        f.line.push(custom_line(
+            &[
+                "R1C1", "R2C2", "R3C3", "R4C4", "R5C5", "R6C6", "R7C7", "R8C8",
+            ],
+            "#F067F0",
+            None,
+        ));
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(
+            b.meta.constraints[0],
+            Constraint::Renban(vec![0, 10, 20, 30, 40, 50, 60, 70])
+        );
+        assert_eq!(
+            b.meta.constraints[1],
+            Constraint::Region(vec![0, 10, 20, 30, 40, 50, 60, 70])
+        );
+        // An 8 cell renban is either 1 to 8 or 2 to 9, so any digit could still be on it.
+        assert_eq!(b.grid[0].count_ones(), 9);
+
+        assert!(b.assign(0, NINE).is_ok());
+        assert_eq!(b.grid[10], TWO | THREE | FOUR | FIVE | SIX | SEVEN | EIGHT);
+
+        let mut f = FPuzzles::new(9);
+        f.line.push(custom_line(&[], "#F067F0", None));
+        let b = Board::try_from(&f).unwrap();
+        assert!(b.meta.constraints.is_empty());
+    }
+
+    #[test]
+    fn palindrome() {
+        let mut f = FPuzzles::new(9);
+        f.palindrome.push(FLine {
+            lines: vec![vec![
+                "R1C1".to_string(),
+                "R2C2".to_string(),
+                "R3C3".to_string(),
+                "R3C4".to_string(),
+                "R3C5".to_string(),
+            ]],
+        });
+        f.grid[0][0].value = Some(7);
+        let b = Board::try_from(&f).unwrap();
+        assert_eq!(
+            b.meta.constraints[0],
+            Constraint::Equal(vec![(0, 22), (10, 21)])
+        );
+        assert_eq!(b.digits()[22], 7);
+
+        // On a three cell line, R1C1 has to match R3C3, which is in the same box.
+        f.palindrome[0].lines[0].truncate(3);
+        assert_eq!(
+            Board::try_from(&f).unwrap_err(),
+            SudokuErrors::Contradiction
+        );
+    }
+
+    #[test]
+    fn palindrome_solutions() {
+        let mut f = FPuzzles::new(4);
+        f.palindrome.push(FLine {
+            lines: vec![vec![
+                "R1C1".to_string(),
+                "R2C2".to_string(),
+                "R3C3".to_string(),
+                "R4C4".to_string(),
+            ]],
+        });
+        let mut b = Board::try_from(&f).unwrap();
+        assert_eq!(b.solution_count_max(1000), 24);
+    }
+
+    #[test]
+    fn between_line() {
+        let mut f = FPuzzles::new(9);
+        f.betweenline.push(FLine {
+            lines: vec![vec![
+                "R1C1".to_string(),
+                "R2C2".to_string(),
+                "R1C3".to_string(),
+            ]],
+        });
+        f.betweenline.push(FLine {
+            lines: vec![vec!["R2C1".to_string(), "R2C2".to_string()]],
+        });
+        f.grid[0][0].value = Some(2);
+        f.grid[0][2].value = Some(5);
+        let b = Board::try_from(&f).unwrap();
+        assert_eq!(b.meta.constraints.len(), 1);
+        assert_eq!(b.meta.constraints[0], Constraint::Between(vec![0, 10, 2]));
+        assert_eq!(b.grid[10], THREE | FOUR);
+    }
+
+    #[test]
+    fn lockout_line() {
+        let mut f = FPuzzles::new(9);
+        f.grid[0][0].value = Some(5);
+        // f-puzzles has no lockout object, so no line colour is read as one.
+        f.line
+            .push(custom_line(&["R1C1", "R2C2", "R3C3"], "#9C9CFF", None));
+        let mut b = Board::try_from(&f).unwrap();
+        assert!(b.meta.constraints.is_empty());
+
+        b.add_line(ExtraLine::Lockout(4), vec![0, 10, 20]).unwrap();
+        b.add_line(ExtraLine::Lockout(7), vec![27, 28, 29]).unwrap();
+        b.add_line(ExtraLine::Lockout(4), vec![40]).unwrap();
+        assert_eq!(
+            b.meta.constraints,
+            vec![
+                Constraint::Lockout(vec![0, 10, 20], 4),
+                Constraint::Lockout(vec![27, 28, 29], 7)
+            ]
+        );
+        assert_eq!(b.grid[20], ONE | NINE);
+        assert_eq!(b.grid[27], ONE | TWO | EIGHT | NINE);
+        assert_eq!(
+            b.add_line(ExtraLine::Lockout(4), vec![80, 81]),
+            Err(SudokuErrors::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn parity_markers() {
+        let mut f = FPuzzles::new(9);
+        f.odd.push(Marker {
+            cell: "R1C1".to_string(),
+        });
+        f.even.push(Marker {
+            cell: "R9C9".to_string(),
+        });
+        let b = Board::try_from(&f).unwrap();
+        assert_eq!(b.grid[0], ONE | THREE | FIVE | SEVEN | NINE);
+        assert_eq!(b.grid[80], TWO | FOUR | SIX | EIGHT);
+
+        f.grid[0][0].value = Some(2);
+        assert_eq!(
+            Board::try_from(&f).unwrap_err(),
+            SudokuErrors::Contradiction
+        );
+
+        // An even bulb of a thermometer is at least 2, so the next cell is at least 3.
+        let mut f = FPuzzles::new(9);
+        f.thermometer.push(FLine {
+            lines: vec![vec!["R1C1".to_string(), "R1C2".to_string()]],
+        });
+        f.even.push(Marker {
+            cell: "R1C1".to_string(),
+        });
+        let b = Board::try_from(&f).unwrap();
+        assert_eq!(b.grid[0], TWO | FOUR | SIX | EIGHT);
+        assert_eq!(b.grid[1], THREE | FOUR | FIVE | SIX | SEVEN | EIGHT | NINE);
+    }
+
+    #[test]
+    fn extremum_markers() {
+        let mut f = FPuzzles::new(9);
+        f.minimum.push(Marker {
+            cell: "R1C1".to_string(),
+        });
+        f.maximum.push(Marker {
+            cell: "R5C5".to_string(),
+        });
+        let all = ONE | TWO | THREE | FOUR | FIVE | SIX | SEVEN | EIGHT | NINE;
+        let b = Board::try_from(&f).unwrap();
+        assert_eq!(b.meta.constraints.len(), 6);
+        assert_eq!(b.grid[0], all & !NINE);
+        assert_eq!(b.grid[40], all & !ONE);
+
+        let mut b = b;
+        b.assign(1, TWO).unwrap();
+        assert_eq!(b.grid[0], ONE);
+        b.assign(31, EIGHT).unwrap();
+        assert_eq!(b.grid[40], NINE);
+        assert_eq!(b.grid[39], all & !(EIGHT | NINE));
+    }
+
+    #[test]
+    fn clone_regions() {
+        let mut f = FPuzzles::new(9);
+        f.clone.push(CloneRegion {
+            cells: vec!["R1C1".to_string(), "R1C2".to_string()],
+            clone_cells: vec!["R5C5".to_string(), "R5C6".to_string()],
+        });
+        f.grid[0][0].value = Some(3);
+        f.grid[4][6].value = Some(7);
+        let b = Board::try_from(&f).unwrap();
+        assert_eq!(
+            b.meta.constraints[0],
+            Constraint::Equal(vec![(0, 40), (1, 41)])
+        );
+        assert_eq!(b.grid[40], THREE);
+        assert_eq!(b.grid[1] & (THREE | SEVEN), 0);
+        assert_eq!(b.grid[1], b.grid[41]);
+
+        f.clone[0].clone_cells.pop();
+        assert_eq!(
+            Board::try_from(&f).unwrap_err(),
+            SudokuErrors::CloneWrongSizes
+        );
+    }
+
+    #[test]
+    fn test_sightline() {
+        assert_eq!(
+            sightline("R2C0", 4),
+            Ok(Sightline {
+                line: Line::Row(1),
+                reversed: false
+            })
+        );
+        assert_eq!(
+            sightline("R2C5", 4),
+            Ok(Sightline {
+                line: Line::Row(1),
+                reversed: true
+            })
+        );
+        assert_eq!(
+            sightline("R5C3", 4),
+            Ok(Sightline {
+                line: Line::Column(2),
+                reversed: true
+            })
+        );
+        assert_eq!(sightline("R2C2", 4), Err(SudokuErrors::BadRCEncoding));
+    }
+
+    #[test]
+    fn xsum_and_skyscraper() {
+        let mut f = FPuzzles::new(4);
+        f.xsum.push(OutsideClue {
+            cell: "R1C5".to_string(),
+            value: Some("10".to_string()),
+        });
+        f.skyscraper.push(OutsideClue {
+            cell: "R0C1".to_string(),
+            value: Some("4".to_string()),
+        });
+        let mut b = Board::try_from(&f).unwrap();
+        // The column reads 1 2 3 4 from the top.
+        for (r, digit) in [ONE, TWO, THREE, FOUR].into_iter().enumerate() {
+            assert_eq!(b.grid[r * 4], digit);
+        }
+        // Seen from the right, the first row has to count off at least 3 cells to add up to 10.
+        assert_eq!(b.grid[3], THREE | FOUR);
+        assert_eq!(b.solution_count_max(1000), 3);
+
+        f.skyscraper[0].value = Some("5".to_string());
+        assert_eq!(
+            Board::try_from(&f).unwrap_err(),
+            SudokuErrors::ValueTooLarge
+        );
+        f.skyscraper[0].value = Some("0".to_string());
+        assert_eq!(
+            Board::try_from(&f).unwrap_err(),
+            SudokuErrors::Contradiction
+        );
+    }
+
+    #[test]
+    fn entropic_and_modular_lines() {
+        let mut f = FPuzzles::new(9);
+        f.grid[0][0].value = Some(2);
+        f.grid[0][1].value = Some(5);
+        f.grid[8][0].value = Some(3);
+        f.grid[8][1].value = Some(4);
+        let mut b = Board::try_from(&f).unwrap();
+        b.add_line(ExtraLine::Entropic, vec![0, 1, 2, 3]).unwrap();
+        b.add_line(ExtraLine::Entropic, vec![9, 10]).unwrap();
+        b.add_line(ExtraLine::Modular, vec![72, 73, 74]).unwrap();
+        assert_eq!(b.meta.constraints.len(), 2);
+        assert_eq!(
+            b.meta.constraints[0],
+            Constraint::Entropic(vec![0, 1, 2, 3])
+        );
+        assert_eq!(b.grid[2], SEVEN | EIGHT | NINE);
+        assert_eq!(b.grid[3], ONE | THREE);
+        assert_eq!(b.grid[74], TWO | FIVE | EIGHT);
+    }
+
+    #[test]
+    fn unlisted_line_colours() {
+        // f-puzzles has no objects for these lines, so no colour is read as one of them.
+        let cells = ["R1C1", "R1C2", "R1C3"];
+        for colour in ["#FFD700", "#A0522D", "#2ECBFF", "#67F068"] {
+            let mut f = FPuzzles::new(9);
+            f.line.push(custom_line(&cells, colour, None));
+            let b = Board::try_from(&f).unwrap();
+            assert!(b.meta.constraints.is_empty());
+        }
+    }
+
+    #[test]
+    fn region_sum_line() {
+        let mut f = FPuzzles::new(9);
+        f.grid[1][3].value = Some(4);
+        let mut b = Board::try_from(&f).unwrap();
+        b.add_line(ExtraLine::RegionSum, vec![10, 11, 12]).unwrap();
+        assert_eq!(
+            b.meta.constraints[0],
+            Constraint::RegionSum(vec![10, 11, 12])
+        );
+        // The segment in the first box has two cells that add up to 4.
+        assert_eq!(b.grid[10], ONE | TWO | THREE);
+        assert_eq!(b.grid[11], ONE | TWO | THREE);
+    }
 }
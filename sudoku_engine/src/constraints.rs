@@ -1,6 +1,6 @@
 //! Implementations related to Sudoku constraints.
 
-use crate::types::{eliminate, Bits, Contradiction, Elimination};
+use crate::types::{eliminate, to_bits, Bits, Contradiction, Elimination};
 
 #[must_use]
 pub(crate) fn check_quad(
@@ -148,102 +148,1877 @@ pub(crate) fn region_enforce_consistency(
     Ok(ret)
 }
 
+pub(crate) const KNIGHT_OFFSETS: [(isize, isize); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+/// Orthogonal neighbours already share a row or column, so only the diagonals are needed for an
+/// antiking constraint.
+pub(crate) const KING_OFFSETS: [(isize, isize); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+
+pub(crate) const ORTHOGONAL_OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// The cells that are a fixed `(row, column)` offset away from `idx`, skipping any that fall off the
+/// grid.
+pub(crate) fn offset_neighbors(
+    idx: usize,
+    size: usize,
+    offsets: &[(isize, isize)],
+) -> impl Iterator<Item = usize> + '_ {
+    let row = idx / size;
+    let column = idx % size;
+    offsets.iter().filter_map(move |(dr, dc)| {
+        let r = row.checked_add_signed(*dr)?;
+        let c = column.checked_add_signed(*dc)?;
+        if r < size && c < size {
+            Some(r * size + c)
+        } else {
+            None
+        }
+    })
+}
+
+pub(crate) fn check_offsets(offsets: &[(isize, isize)], size: usize, grid: &[Bits]) -> bool {
+    for idx in 0..grid.len() {
+        for i in offset_neighbors(idx, size, offsets) {
+            if grid[idx] & grid[i] != 0 {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+pub(crate) fn offsets_enforce_consistency(
+    idx: usize,
+    value: Bits,
+    offsets: &[(isize, isize)],
+    size: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let mut ret = Elimination::Same;
+    for i in offset_neighbors(idx, size, offsets) {
+        ret &= eliminate(i, value, grid)?;
+    }
+
+    Ok(ret)
+}
+
+/// The digits that are `diff` more or `diff` less than any digit in `value`. The 0th bit isn't a
+/// digit, so it is masked off.
+fn differ_by(value: Bits, diff: usize) -> Bits {
+    ((value << diff) | (value >> diff)) & !1
+}
+
+/// The digits that are `ratio` times, or `1 / ratio` times, any digit in `value`.
+fn ratio_of(value: Bits, ratio: usize) -> Bits {
+    let mut ret = 0;
+    let mut remaining = value;
+    while remaining != 0 {
+        let d = remaining.trailing_zeros() as usize;
+        if let Some(product) = d.checked_mul(ratio).filter(|x| *x < Bits::BITS as usize) {
+            ret |= 1 << product;
+        }
+        if d.is_multiple_of(ratio) {
+            ret |= 1 << (d / ratio);
+        }
+        remaining &= remaining - 1;
+    }
+
+    ret & !1
+}
+
+/// The digits that add up to `total` with any digit in `value`.
+fn sum_to(value: Bits, total: usize) -> Bits {
+    let mut ret = 0;
+    let mut remaining = value;
+    while remaining != 0 {
+        let d = remaining.trailing_zeros() as usize;
+        if d < total {
+            ret |= 1 << (total - d);
+        }
+        remaining &= remaining - 1;
+    }
+
+    ret & !1
+}
+
+/// The digits that are related to a digit in `value` by any of the parameters in `params`.
+fn related_by_any(value: Bits, params: Bits, related: fn(Bits, usize) -> Bits) -> Bits {
+    let mut ret = 0;
+    let mut remaining = params;
+    while remaining != 0 {
+        ret |= related(value, remaining.trailing_zeros() as usize);
+        remaining &= remaining - 1;
+    }
+
+    ret
+}
+
+pub(crate) fn check_nonconsecutive(size: usize, grid: &[Bits]) -> bool {
+    for idx in 0..grid.len() {
+        for i in offset_neighbors(idx, size, &ORTHOGONAL_OFFSETS) {
+            if differ_by(grid[idx], 1) & grid[i] != 0 {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+pub(crate) fn nonconsecutive_enforce_consistency(
+    idx: usize,
+    value: Bits,
+    size: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    offsets_enforce_consistency(idx, differ_by(value, 1), &ORTHOGONAL_OFFSETS, size, grid)
+}
+
+/// Remove candidates from a pair of cells that have no partner in the other cell. `related` must be
+/// symmetric, and give the digits that can pair with any of the digits passed to it.
+fn pair_enforce_consistency(
+    a: usize,
+    b: usize,
+    related: impl Fn(Bits) -> Bits,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let mut ret = eliminate(b, grid[b] & !related(grid[a]), grid)?;
+    ret &= eliminate(a, grid[a] & !related(grid[b]), grid)?;
+    Ok(ret)
+}
+
+pub(crate) fn check_difference(a: usize, b: usize, diff: usize, grid: &[Bits]) -> bool {
+    differ_by(grid[a], diff) & grid[b] != 0
+}
+
+pub(crate) fn difference_enforce_consistency(
+    a: usize,
+    b: usize,
+    diff: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    pair_enforce_consistency(a, b, |v| differ_by(v, diff), grid)
+}
+
+pub(crate) fn check_ratio(a: usize, b: usize, ratio: usize, grid: &[Bits]) -> bool {
+    ratio_of(grid[a], ratio) & grid[b] != 0
+}
+
+pub(crate) fn ratio_enforce_consistency(
+    a: usize,
+    b: usize,
+    ratio: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    pair_enforce_consistency(a, b, |v| ratio_of(v, ratio), grid)
+}
+
+pub(crate) fn check_sum(a: usize, b: usize, total: usize, grid: &[Bits]) -> bool {
+    sum_to(grid[a], total) & grid[b] != 0
+}
+
+pub(crate) fn sum_enforce_consistency(
+    a: usize,
+    b: usize,
+    total: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    // The two cells are adjacent, so they share a row or column and can't both be half the total.
+    let mut ret = Elimination::Same;
+    if total.is_multiple_of(2) {
+        ret &= eliminate(a, to_bits(total / 2), grid)?;
+        ret &= eliminate(b, to_bits(total / 2), grid)?;
+    }
+    ret &= pair_enforce_consistency(a, b, |v| sum_to(v, total), grid)?;
+    Ok(ret)
+}
+
+fn is_dotted(a: usize, b: usize, dotted: &[(usize, usize)]) -> bool {
+    dotted.contains(&(a.min(b), a.max(b)))
+}
+
+/// Check that no orthogonally adjacent pair without a dot has digits that are `related`.
+fn check_negative(
+    dotted: &[(usize, usize)],
+    related: impl Fn(Bits) -> Bits,
+    size: usize,
+    grid: &[Bits],
+) -> bool {
+    for idx in 0..grid.len() {
+        for i in offset_neighbors(idx, size, &ORTHOGONAL_OFFSETS) {
+            if !is_dotted(idx, i, dotted) && related(grid[idx]) & grid[i] != 0 {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// Remove `forbidden` from the orthogonal neighbours of `idx` that don't share a dot with it.
+fn negative_enforce_consistency(
+    idx: usize,
+    forbidden: Bits,
+    dotted: &[(usize, usize)],
+    size: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let mut ret = Elimination::Same;
+    for i in offset_neighbors(idx, size, &ORTHOGONAL_OFFSETS) {
+        if !is_dotted(idx, i, dotted) {
+            ret &= eliminate(i, forbidden, grid)?;
+        }
+    }
+
+    Ok(ret)
+}
+
+pub(crate) fn check_negative_difference(
+    dotted: &[(usize, usize)],
+    differences: Bits,
+    size: usize,
+    grid: &[Bits],
+) -> bool {
+    check_negative(
+        dotted,
+        |v| related_by_any(v, differences, differ_by),
+        size,
+        grid,
+    )
+}
+
+pub(crate) fn negative_difference_enforce_consistency(
+    idx: usize,
+    value: Bits,
+    dotted: &[(usize, usize)],
+    differences: Bits,
+    size: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let forbidden = related_by_any(value, differences, differ_by);
+    negative_enforce_consistency(idx, forbidden, dotted, size, grid)
+}
+
+pub(crate) fn check_negative_ratio(
+    dotted: &[(usize, usize)],
+    ratios: Bits,
+    size: usize,
+    grid: &[Bits],
+) -> bool {
+    check_negative(dotted, |v| related_by_any(v, ratios, ratio_of), size, grid)
+}
+
+pub(crate) fn negative_ratio_enforce_consistency(
+    idx: usize,
+    value: Bits,
+    dotted: &[(usize, usize)],
+    ratios: Bits,
+    size: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let forbidden = related_by_any(value, ratios, ratio_of);
+    negative_enforce_consistency(idx, forbidden, dotted, size, grid)
+}
+
+pub(crate) fn check_negative_sum(
+    marked: &[(usize, usize)],
+    totals: Bits,
+    size: usize,
+    grid: &[Bits],
+) -> bool {
+    check_negative(marked, |v| related_by_any(v, totals, sum_to), size, grid)
+}
+
+pub(crate) fn negative_sum_enforce_consistency(
+    idx: usize,
+    value: Bits,
+    marked: &[(usize, usize)],
+    totals: Bits,
+    size: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let forbidden = related_by_any(value, totals, sum_to);
+    negative_enforce_consistency(idx, forbidden, marked, size, grid)
+}
+
+/// Every set of `len` distinct digits, up to `max_val`, that add up to `total`.
+pub(crate) fn cage_combinations(len: usize, total: usize, max_val: usize) -> Vec<Bits> {
+    let mut ret = Vec::new();
+    for mask in 0..(1 << max_val) {
+        let digits: Bits = mask << 1;
+        if digits.count_ones() as usize != len {
+            continue;
+        }
+        let mut sum = 0;
+        let mut remaining = digits;
+        while remaining != 0 {
+            sum += remaining.trailing_zeros() as usize;
+            remaining &= remaining - 1;
+        }
+        if sum == total {
+            ret.push(digits);
+        }
+    }
+
+    ret
+}
+
+/// The digits in a completed cage must be one of the combinations that add up to the total.
+pub(crate) fn check_killer(cage: &[usize], combinations: &[Bits], grid: &[Bits]) -> bool {
+    let digits = cage.iter().fold(0, |acc, idx| acc | grid[*idx]);
+    combinations.contains(&digits)
+}
+
+/// Remove candidates from a cage that aren't in any combination that still fits. A combination
+/// fits when each cell has a candidate from it, and each of its digits can go in some cell.
+pub(crate) fn killer_enforce_consistency(
+    cage: &[usize],
+    combinations: &[Bits],
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let available = cage.iter().fold(0, |acc, idx| acc | grid[*idx]);
+    let mut allowed = 0;
+    for c in combinations {
+        if c & available == *c && cage.iter().all(|idx| grid[*idx] & c != 0) {
+            allowed |= c;
+        }
+    }
+    if allowed == 0 {
+        return Err(Contradiction(()));
+    }
+
+    let mut ret = Elimination::Same;
+    for idx in cage {
+        ret &= eliminate(*idx, grid[*idx] & !allowed, grid)?;
+    }
+
+    Ok(ret)
+}
+
+/// The smallest digit in `value`.
+fn min_digit(value: Bits) -> usize {
+    value.trailing_zeros() as usize
+}
+
+/// The largest digit in `value`.
+fn max_digit(value: Bits) -> usize {
+    (Bits::BITS - 1 - value.leading_zeros()) as usize
+}
+
+/// All of the digits up to and including `d`.
+fn up_to(d: usize) -> Bits {
+    if d + 1 >= Bits::BITS as usize {
+        Bits::MAX
+    } else {
+        (1 << (d + 1)) - 1
+    }
+}
+
+/// Read a list of digits as a number, the way it would be written down.
+fn concatenate(digits: impl Iterator<Item = usize>) -> usize {
+    digits.fold(0, |acc, d| {
+        let mut shift = 10;
+        while shift <= d {
+            shift *= 10;
+        }
+        acc * shift + d
+    })
+}
+
+/// Digits strictly increase along a thermometer, starting from the bulb.
+pub(crate) fn check_thermo(thermo: &[usize], grid: &[Bits]) -> bool {
+    thermo.windows(2).all(|w| grid[w[0]] < grid[w[1]])
+}
+
+/// Tighten the bounds of each cell on a thermometer. A cell must be bigger than the smallest
+/// candidate before it, and smaller than the largest candidate after it.
+pub(crate) fn thermo_enforce_consistency(
+    thermo: &[usize],
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let mut ret = Elimination::Same;
+    for w in thermo.windows(2) {
+        ret &= eliminate(w[1], up_to(min_digit(grid[w[0]])), grid)?;
+    }
+    for w in thermo.windows(2).rev() {
+        ret &= eliminate(w[0], !up_to(max_digit(grid[w[1]]) - 1), grid)?;
+    }
+
+    Ok(ret)
+}
+
+/// The digits that are at least `n` away from any digit in `value`.
+fn at_least_apart(value: Bits, n: usize) -> Bits {
+    let mut ret = !up_to(min_digit(value) + n - 1);
+    if max_digit(value) > n {
+        ret |= up_to(max_digit(value) - n);
+    }
+
+    ret & !1
+}
+
+/// Adjacent digits along a whisper line differ by at least `n`.
+pub(crate) fn check_whisper(line: &[usize], n: usize, grid: &[Bits]) -> bool {
+    line.windows(2)
+        .all(|w| at_least_apart(grid[w[0]], n) & grid[w[1]] != 0)
+}
+
+pub(crate) fn whisper_enforce_consistency(
+    line: &[usize],
+    n: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let mut ret = Elimination::Same;
+    for w in line.windows(2) {
+        ret &= pair_enforce_consistency(w[0], w[1], |v| at_least_apart(v, n), grid)?;
+    }
+
+    Ok(ret)
+}
+
+/// The digits on a renban line are a set of consecutive digits, in any order.
+pub(crate) fn check_renban(line: &[usize], grid: &[Bits]) -> bool {
+    let digits = line.iter().fold(0, |acc, idx| acc | grid[*idx]);
+    digits.count_ones() as usize == line.len()
+        && digits >> min_digit(digits) == up_to(line.len() - 1)
+}
+
+/// Keep the digits that fall in some window of `line.len()` consecutive digits that can still be
+/// placed on the line. A window works if every cell has a candidate from it, and every digit in it
+/// has a cell it can go in.
+pub(crate) fn renban_enforce_consistency(
+    line: &[usize],
+    max_val: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let available = line.iter().fold(0, |acc, idx| acc | grid[*idx]);
+    let mut allowed = 0;
+    for low in 1..=(max_val + 1).saturating_sub(line.len()) {
+        let window = up_to(line.len() - 1) << low;
+        if window & available == window && line.iter().all(|idx| grid[*idx] & window != 0) {
+            allowed |= window;
+        }
+    }
+    if allowed == 0 {
+        return Err(Contradiction(()));
+    }
+
+    let mut ret = Elimination::Same;
+    for idx in line {
+        ret &= eliminate(*idx, grid[*idx] & !allowed, grid)?;
+    }
+
+    Ok(ret)
+}
+
+/// Each pair of cells holds the same digit.
+pub(crate) fn check_equal(pairs: &[(usize, usize)], grid: &[Bits]) -> bool {
+    pairs.iter().all(|(a, b)| grid[*a] == grid[*b])
+}
+
+/// Limit both cells of each pair to the candidates they have in common.
+pub(crate) fn equal_enforce_consistency(
+    pairs: &[(usize, usize)],
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let mut ret = Elimination::Same;
+    for (a, b) in pairs {
+        ret &= eliminate(*a, grid[*a] & !grid[*b], grid)?;
+        ret &= eliminate(*b, grid[*b] & !grid[*a], grid)?;
+    }
+
+    Ok(ret)
+}
+
+/// The digits strictly between `low` and `high`.
+fn between(low: usize, high: usize) -> Bits {
+    if high <= low + 1 {
+        return 0;
+    }
+    up_to(high - 1) & !up_to(low)
+}
+
+/// The digits in the middle of a between line are strictly between the digits at its ends.
+pub(crate) fn check_between(line: &[usize], grid: &[Bits]) -> bool {
+    let (Some(a), Some(b)) = (line.first(), line.last()) else {
+        return true;
+    };
+    let (low, high) = (grid[*a].min(grid[*b]), grid[*a].max(grid[*b]));
+    line[1..line.len() - 1]
+        .iter()
+        .all(|idx| low < grid[*idx] && grid[*idx] < high)
+}
+
+pub(crate) fn between_enforce_consistency(
+    line: &[usize],
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let (a, b) = (line[0], line[line.len() - 1]);
+    let middle = &line[1..line.len() - 1];
+    if middle.is_empty() {
+        return Ok(Elimination::Same);
+    }
+
+    // The low end has to be below every middle cell, and the high end above them.
+    let low_cap = middle
+        .iter()
+        .map(|i| max_digit(grid[*i]))
+        .min()
+        .unwrap_or(0);
+    let high_floor = middle
+        .iter()
+        .map(|i| min_digit(grid[*i]))
+        .max()
+        .unwrap_or(0);
+    let can_be_low = up_to(low_cap - 1) & !1;
+    let can_be_high = !up_to(high_floor);
+
+    let mut ret = Elimination::Same;
+    for (end, other) in [(a, b), (b, a)] {
+        let mut allowed = 0;
+        let other_high = grid[other] & can_be_high;
+        if other_high != 0 {
+            allowed |= can_be_low & up_to(max_digit(other_high) - 1);
+        }
+        let other_low = grid[other] & can_be_low;
+        if other_low != 0 {
+            allowed |= can_be_high & !up_to(min_digit(other_low));
+        }
+        ret &= eliminate(end, grid[end] & !allowed, grid)?;
+    }
+
+    let allowed = between(min_digit(grid[a]), max_digit(grid[b]))
+        | between(min_digit(grid[b]), max_digit(grid[a]));
+    for idx in middle {
+        ret &= eliminate(*idx, grid[*idx] & !allowed, grid)?;
+    }
+
+    Ok(ret)
+}
+
+/// The ends of a lockout line differ by at least `n`, and the digits in the middle are outside of
+/// the range of the ends.
+pub(crate) fn check_lockout(line: &[usize], n: usize, grid: &[Bits]) -> bool {
+    let (Some(a), Some(b)) = (line.first(), line.last()) else {
+        return true;
+    };
+    let (low, high) = (grid[*a].min(grid[*b]), grid[*a].max(grid[*b]));
+    at_least_apart(low, n) & high != 0
+        && line[1..line.len() - 1]
+            .iter()
+            .all(|idx| grid[*idx] < low || high < grid[*idx])
+}
+
+pub(crate) fn lockout_enforce_consistency(
+    line: &[usize],
+    n: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let (a, b) = (line[0], line[line.len() - 1]);
+    let mut ret = pair_enforce_consistency(a, b, |v| at_least_apart(v, n), grid)?;
+
+    // Whichever way round the ends go, the low end is at most the smaller of the two largest
+    // candidates, and the high end is at least the larger of the two smallest.
+    let low = max_digit(grid[a]).min(max_digit(grid[b]));
+    let high = min_digit(grid[a]).max(min_digit(grid[b]));
+    if low <= high {
+        let locked = up_to(high) & !up_to(low - 1);
+        for idx in &line[1..line.len() - 1] {
+            ret &= eliminate(*idx, locked, grid)?;
+        }
+    }
+
+    Ok(ret)
+}
+
+/// Low, middle and high digits, for entropic lines.
+pub(crate) fn entropic_classes(max_val: usize) -> [Bits; 3] {
+    let mut classes = [0; 3];
+    for d in 1..=max_val {
+        classes[(d - 1) * 3 / max_val] |= to_bits(d);
+    }
+    classes
+}
+
+/// Digits grouped by their remainder when divided by 3, for modular lines.
+pub(crate) fn modular_classes(max_val: usize) -> [Bits; 3] {
+    let mut classes = [0; 3];
+    for d in 1..=max_val {
+        classes[d % 3] |= to_bits(d);
+    }
+    classes
+}
+
+/// The classes that some candidate of `value` belongs to, as a mask over `classes`.
+fn classes_of(value: Bits, classes: &[Bits; 3]) -> usize {
+    (0..3)
+        .filter(|c| value & classes[*c] != 0)
+        .fold(0, |acc, c| acc | 1 << c)
+}
+
+/// Every run of 3 cells on the line has a digit from each class.
+pub(crate) fn check_classes(line: &[usize], classes: &[Bits; 3], grid: &[Bits]) -> bool {
+    line.windows(3).all(|w| {
+        w.iter()
+            .fold(0, |acc, idx| acc | classes_of(grid[*idx], classes))
+            == 0b111
+    })
+}
+
+/// Every third cell along the line is in the same class, and the first three cells are all in
+/// different classes. Restrict each cell to the classes its position can still be in.
+pub(crate) fn classes_enforce_consistency(
+    line: &[usize],
+    classes: &[Bits; 3],
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    if line.len() < 3 {
+        return Ok(Elimination::Same);
+    }
+
+    let mut positions = [0b111; 3];
+    for (i, idx) in line.iter().enumerate() {
+        positions[i % 3] &= classes_of(grid[*idx], classes);
+    }
+    // A position that is down to one class takes it away from the others.
+    for i in 0..3 {
+        if positions[i].count_ones() == 1 {
+            for j in (0..3).filter(|j| *j != i) {
+                positions[j] &= !positions[i];
+            }
+        }
+    }
+
+    let mut ret = Elimination::Same;
+    for (i, idx) in line.iter().enumerate() {
+        let allowed = (0..3)
+            .filter(|c| positions[i % 3] & 1 << c != 0)
+            .fold(0, |acc, c| acc | classes[c]);
+        ret &= eliminate(*idx, grid[*idx] & !allowed, grid)?;
+    }
+
+    Ok(ret)
+}
+
+/// Split a line into the segments it makes as it passes through each region.
+pub(crate) fn region_segments<'a>(line: &'a [usize], regions: &[Vec<usize>]) -> Vec<&'a [usize]> {
+    let region_of = |idx: &usize| regions.iter().position(|r| r.contains(idx));
+    let mut ret = Vec::new();
+    let mut start = 0;
+    for i in 1..=line.len() {
+        if i == line.len() || region_of(&line[i]) != region_of(&line[start]) {
+            ret.push(&line[start..i]);
+            start = i;
+        }
+    }
+    ret
+}
+
+/// Each segment of the line adds up to the same total.
+pub(crate) fn check_region_sum(segments: &[&[usize]], grid: &[Bits]) -> bool {
+    let mut sums = segments
+        .iter()
+        .map(|s| s.iter().map(|idx| min_digit(grid[*idx])).sum::<usize>());
+    let Some(first) = sums.next() else {
+        return true;
+    };
+    sums.all(|sum| sum == first)
+}
+
+/// Bound every segment by the totals that all of the segments can reach. Digits in a segment are
+/// in the same region, so they can't repeat.
+pub(crate) fn region_sum_enforce_consistency(
+    segments: &[&[usize]],
+    max_val: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    if segments.len() < 2 {
+        return Ok(Elimination::Same);
+    }
+
+    let mut low = 0;
+    let mut high = usize::MAX;
+    for segment in segments {
+        let (distinct_min, distinct_max) =
+            distinct_sum_range(segment.len(), 1, max_val).ok_or(Contradiction(()))?;
+        let cells_min: usize = segment.iter().map(|idx| min_digit(grid[*idx])).sum();
+        let cells_max: usize = segment.iter().map(|idx| max_digit(grid[*idx])).sum();
+        low = low.max(distinct_min).max(cells_min);
+        high = high.min(distinct_max).min(cells_max);
+    }
+    if low > high {
+        return Err(Contradiction(()));
+    }
+
+    let mut ret = Elimination::Same;
+    for segment in segments {
+        ret &= bound_sum(segment, low, high, grid)?;
+    }
+
+    Ok(ret)
+}
+
+/// The number in the circle of an arrow is the sum of the digits along its shaft. A circle with
+/// several cells is read as a multi-digit number.
+pub(crate) fn check_arrow(circle: &[usize], shaft: &[usize], grid: &[Bits]) -> bool {
+    let total = concatenate(circle.iter().map(|i| min_digit(grid[*i])));
+    total == shaft.iter().map(|i| min_digit(grid[*i])).sum()
+}
+
+/// Bound the circle by the smallest and largest possible sums of the shaft, and bound each shaft
+/// cell by what the circle allows after the rest of the shaft is accounted for. Digits can repeat
+/// on a shaft, so each cell is bounded independently.
+pub(crate) fn arrow_enforce_consistency(
+    circle: &[usize],
+    shaft: &[usize],
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let shaft_min: usize = shaft.iter().map(|i| min_digit(grid[*i])).sum();
+    let shaft_max: usize = shaft.iter().map(|i| max_digit(grid[*i])).sum();
+
+    let mut ret = Elimination::Same;
+    for (j, idx) in circle.iter().enumerate() {
+        let mut remaining = grid[*idx];
+        while remaining != 0 {
+            let d = min_digit(remaining);
+            remaining &= remaining - 1;
+            let with = |bound: fn(Bits) -> usize| {
+                concatenate(
+                    circle
+                        .iter()
+                        .enumerate()
+                        .map(|(k, i)| if k == j { d } else { bound(grid[*i]) }),
+                )
+            };
+            if with(min_digit) > shaft_max || with(max_digit) < shaft_min {
+                ret &= eliminate(*idx, 1 << d, grid)?;
+            }
+        }
+    }
+
+    let circle_min = concatenate(circle.iter().map(|i| min_digit(grid[*i])));
+    let circle_max = concatenate(circle.iter().map(|i| max_digit(grid[*i])));
+    ret &= bound_sum(shaft, circle_min, circle_max, grid)?;
+
+    Ok(ret)
+}
+
+/// Bound each of `cells` so that their sum can land between `low` and `high`. The cells are
+/// bounded independently, so this is safe to use when digits can repeat.
+fn bound_sum(
+    cells: &[usize],
+    low: usize,
+    high: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let cells_min: usize = cells.iter().map(|i| min_digit(grid[*i])).sum();
+    let cells_max: usize = cells.iter().map(|i| max_digit(grid[*i])).sum();
+    if high < cells_min || low > cells_max {
+        return Err(Contradiction(()));
+    }
+
+    let mut ret = Elimination::Same;
+    for idx in cells {
+        let others_min = cells_min - min_digit(grid[*idx]);
+        let others_max = cells_max - max_digit(grid[*idx]);
+        ret &= eliminate(*idx, !up_to(high - others_min), grid)?;
+        if low > others_max {
+            ret &= eliminate(*idx, up_to(low - others_max - 1), grid)?;
+        }
+    }
+
+    Ok(ret)
+}
+
+/// The digits along a little killer diagonal add up to the total. Digits may repeat.
+pub(crate) fn check_little_killer(diagonal: &[usize], total: usize, grid: &[Bits]) -> bool {
+    diagonal.iter().map(|i| min_digit(grid[*i])).sum::<usize>() == total
+}
+
+pub(crate) fn little_killer_enforce_consistency(
+    diagonal: &[usize],
+    total: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    bound_sum(diagonal, total, total, grid)
+}
+
+/// The smallest and largest sums of `count` distinct digits between `low` and `high`, inclusive.
+/// Returns `None` if there aren't enough digits.
+fn distinct_sum_range(count: usize, low: usize, high: usize) -> Option<(usize, usize)> {
+    if low + count > high + 1 {
+        return None;
+    }
+    let min = (low..low + count).sum();
+    let max = (high + 1 - count..=high).sum();
+    Some((min, max))
+}
+
+/// The sum of the digits between the lowest and highest digits on a line.
+pub(crate) fn check_sandwich(line: &[usize], total: usize, max_val: usize, grid: &[Bits]) -> bool {
+    let crusts = (1 << 1) | (1 << max_val);
+    let mut positions = line
+        .iter()
+        .enumerate()
+        .filter(|(_, idx)| grid[**idx] & crusts != 0)
+        .map(|(i, _)| i);
+    let (Some(a), Some(b)) = (positions.next(), positions.next()) else {
+        return false;
+    };
+    let sum: usize = line[a + 1..b].iter().map(|idx| min_digit(grid[*idx])).sum();
+    sum == total
+}
+
+/// Could the crusts of a sandwich go at positions `a` and `b` of a line?
+fn sandwich_fits(
+    line: &[usize],
+    a: usize,
+    b: usize,
+    total: usize,
+    max_val: usize,
+    grid: &[Bits],
+) -> bool {
+    let crusts = (1 << 1) | (1 << max_val);
+    let between = &line[a.min(b) + 1..a.max(b)];
+    let Some((mut lower, mut upper)) = distinct_sum_range(between.len(), 2, max_val - 1) else {
+        return false;
+    };
+    let mut cells_min = 0;
+    let mut cells_max = 0;
+    for idx in between {
+        let filling = grid[*idx] & !crusts;
+        if filling == 0 {
+            return false;
+        }
+        cells_min += min_digit(filling);
+        cells_max += max_digit(filling);
+    }
+    lower = lower.max(cells_min);
+    upper = upper.min(cells_max);
+
+    lower <= total && total <= upper
+}
+
+/// Remove the lowest and highest digits from the cells of a line where they can't be the crusts of
+/// a sandwich with the right total.
+pub(crate) fn sandwich_enforce_consistency(
+    line: &[usize],
+    total: usize,
+    max_val: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let low = 1 << 1;
+    let high = 1 << max_val;
+    let mut low_fits = vec![false; line.len()];
+    let mut high_fits = vec![false; line.len()];
+    for a in 0..line.len() {
+        if grid[line[a]] & low == 0 {
+            continue;
+        }
+        for b in 0..line.len() {
+            if a == b || grid[line[b]] & high == 0 {
+                continue;
+            }
+            if sandwich_fits(line, a, b, total, max_val, grid) {
+                low_fits[a] = true;
+                high_fits[b] = true;
+            }
+        }
+    }
+
+    let mut ret = Elimination::Same;
+    for (i, idx) in line.iter().enumerate() {
+        if !low_fits[i] {
+            ret &= eliminate(*idx, low, grid)?;
+        }
+        if !high_fits[i] {
+            ret &= eliminate(*idx, high, grid)?;
+        }
+    }
+
+    Ok(ret)
+}
+
+/// The sum of the first X digits seen from an outside clue, where X is the first of those digits.
+pub(crate) fn check_xsum(line: &[usize], total: usize, grid: &[Bits]) -> bool {
+    let x = min_digit(grid[line[0]]);
+    x <= line.len() && line[..x].iter().map(|i| min_digit(grid[*i])).sum::<usize>() == total
+}
+
+/// Remove the digits from the first cell that can't count off a sum with the right total. Once the
+/// first digit is known, the cells it counts off are bounded by the rest of the total.
+pub(crate) fn xsum_enforce_consistency(
+    line: &[usize],
+    total: usize,
+    max_val: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let first = line[0];
+    let mut ret = Elimination::Same;
+    for x in 1..=max_val.min(line.len()) {
+        if grid[first] & to_bits(x) == 0 {
+            continue;
+        }
+        let counted = &line[1..x];
+        let cells_min: usize = counted.iter().map(|i| min_digit(grid[*i])).sum();
+        let cells_max: usize = counted.iter().map(|i| max_digit(grid[*i])).sum();
+        let fits = distinct_sum_range(counted.len(), 1, max_val).is_some_and(|(low, high)| {
+            x + low.max(cells_min) <= total && total <= x + high.min(cells_max)
+        });
+        if !fits {
+            ret &= eliminate(first, to_bits(x), grid)?;
+        }
+    }
+    for x in max_val.min(line.len()) + 1..=max_val {
+        ret &= eliminate(first, to_bits(x), grid)?;
+    }
+
+    if grid[first].count_ones() == 1 {
+        let x = min_digit(grid[first]);
+        ret &= bound_sum(&line[1..x], total - x, total - x, grid)?;
+    }
+
+    Ok(ret)
+}
+
+/// The number of digits seen from an outside clue, where taller digits hide shorter ones behind
+/// them.
+pub(crate) fn check_skyscraper(line: &[usize], seen: usize, grid: &[Bits]) -> bool {
+    let mut tallest = 0;
+    let mut count = 0;
+    for idx in line {
+        if grid[*idx] > tallest {
+            tallest = grid[*idx];
+            count += 1;
+        }
+    }
+    count == seen
+}
+
+/// Widen a range of counts to include another one.
+fn merge_range(range: &mut Option<(usize, usize)>, low: usize, high: usize) {
+    *range = Some(match *range {
+        Some((l, h)) => (l.min(low), h.max(high)),
+        None => (low, high),
+    });
+}
+
+/// Remove digits that can't be part of a line with the right number of skyscrapers seen. For each
+/// cell, we track the fewest and most skyscrapers that can be seen before and after it, for each
+/// height of the tallest skyscraper so far. Digits may repeat, so this is a relaxation.
+pub(crate) fn skyscraper_enforce_consistency(
+    line: &[usize],
+    seen: usize,
+    max_val: usize,
+    grid: &mut [Bits],
+) -> Result<Elimination, Contradiction> {
+    let heights = max_val + 1;
+    let step = |tallest: usize, d: usize| if d > tallest { (d, 1) } else { (tallest, 0) };
+
+    // before[i][t] is the range seen in the first i cells, given the tallest of them is t.
+    let mut before = vec![vec![None; heights]; line.len() + 1];
+    before[0][0] = Some((0, 0));
+    for (i, idx) in line.iter().enumerate() {
+        for t in 0..heights {
+            let Some((low, high)) = before[i][t] else {
+                continue;
+            };
+            for d in (1..=max_val).filter(|d| grid[*idx] & to_bits(*d) != 0) {
+                let (next, add) = step(t, d);
+                merge_range(&mut before[i + 1][next], low + add, high + add);
+            }
+        }
+    }
+
+    // after[i][t] is the range seen from cell i onwards, given the tallest before it is t. Every
+    // digit is in the line somewhere, so it has to end with the tallest skyscraper seen.
+    let mut after = vec![vec![None; heights]; line.len() + 1];
+    after[line.len()][max_val] = Some((0, 0));
+    for (i, idx) in line.iter().enumerate().rev() {
+        for t in 0..heights {
+            for d in (1..=max_val).filter(|d| grid[*idx] & to_bits(*d) != 0) {
+                let (next, add) = step(t, d);
+                if let Some((low, high)) = after[i + 1][next] {
+                    merge_range(&mut after[i][t], low + add, high + add);
+                }
+            }
+        }
+    }
+
+    let mut ret = Elimination::Same;
+    for (i, idx) in line.iter().enumerate() {
+        let cell = grid[*idx];
+        for d in (1..=max_val).filter(|d| cell & to_bits(*d) != 0) {
+            let fits = (0..heights).any(|t| {
+                let (next, add) = step(t, d);
+                match (before[i][t], after[i + 1][next]) {
+                    (Some((bl, bh)), Some((al, ah))) => {
+                        bl + add + al <= seen && seen <= bh + add + ah
+                    }
+                    _ => false,
+                }
+            });
+            if !fits {
+                ret &= eliminate(*idx, to_bits(d), grid)?;
+            }
+        }
+    }
+
+    Ok(ret)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const ONE: Bits = 1 << 1;
-    const TWO: Bits = 1 << 2;
-    const THREE: Bits = 1 << 3;
-    const FOUR: Bits = 1 << 4;
-    const FIVE: Bits = 1 << 5;
-    const SIX: Bits = 1 << 6;
-    const SEVEN: Bits = 1 << 7;
-    const EIGHT: Bits = 1 << 8;
-    const NINE: Bits = 1 << 9;
-    const ALL_DIGITS: Bits = ONE | TWO | THREE | FOUR | FIVE | SIX | SEVEN | EIGHT | NINE;
+    const ONE: Bits = 1 << 1;
+    const TWO: Bits = 1 << 2;
+    const THREE: Bits = 1 << 3;
+    const FOUR: Bits = 1 << 4;
+    const FIVE: Bits = 1 << 5;
+    const SIX: Bits = 1 << 6;
+    const SEVEN: Bits = 1 << 7;
+    const EIGHT: Bits = 1 << 8;
+    const NINE: Bits = 1 << 9;
+    const ALL_DIGITS: Bits = ONE | TWO | THREE | FOUR | FIVE | SIX | SEVEN | EIGHT | NINE;
+
+    #[test]
+    fn test_check_quad() {
+        let mut grid = [0; 81];
+        grid[11] = FOUR;
+        grid[12] = TWO;
+        grid[20] = ONE;
+        grid[21] = THREE;
+
+        let mut vals = ONE | TWO | THREE | FOUR;
+        assert!(check_quad(11, vals, 0, 9, &grid));
+        assert!(check_quad(11, TWO | THREE, 0, 9, &grid));
+
+        vals = TWO | THREE | FOUR | FIVE;
+        assert!(!check_quad(11, vals, 0, 9, &grid));
+        assert!(!check_quad(11, TWO | THREE, FOUR, 9, &grid));
+    }
+
+    #[test]
+    fn test_check_quad_repeated_digit_in_solution() {
+        let grid = [
+            32, 64, 8, 2, 16, 4, 4, 16, 2, 64, 32, 8, 16, 32, 4, 8, 64, 2, 8, 2, 64, 16, 4, 32, 64,
+            8, 32, 4, 2, 16, 2, 4, 16, 32, 8, 64,
+        ];
+        assert!(check_quad(4, THREE | FOUR, 0, 6, &grid));
+        assert!(check_quad(8, ONE | TWO | THREE | SIX, 0, 6, &grid));
+        assert!(check_quad(9, FIVE | SIX, 0, 6, &grid));
+        assert!(check_quad(12, ONE | THREE | FIVE, 0, 6, &grid));
+        assert!(check_quad(19, ONE | THREE | FIVE | SIX, 0, 6, &grid));
+        assert!(check_quad(22, ONE | FIVE, 0, 6, &grid));
+    }
+
+    #[test]
+    fn test_init_quad() {
+        let mut grid = [ALL_DIGITS; 81];
+
+        assert_eq!(
+            init_quad(0, ONE | TWO | THREE | FOUR, 0, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ONE | TWO | THREE | FOUR);
+
+        grid[0] = NINE;
+        assert!(init_quad(0, ONE | TWO | THREE | FOUR, 0, 9, &mut grid).is_err());
+
+        grid[2] = FIVE;
+        grid[12] = FOUR;
+        assert!(init_quad(2, FIVE | SIX | SEVEN | EIGHT, 0, 9, &mut grid).is_err());
+
+        assert_eq!(init_quad(4, FIVE, SIX, 9, &mut grid), Ok(Elimination::Same));
+
+        grid[4] = FIVE;
+        grid[5] = SIX;
+        grid[13] = SIX;
+        grid[14] = FIVE;
+        assert!(init_quad(4, FOUR | FIVE, 0, 9, &mut grid).is_err());
+    }
+
+    #[test]
+    fn test_offset_neighbors() {
+        let mut corner: Vec<usize> = offset_neighbors(0, 9, &KNIGHT_OFFSETS).collect();
+        corner.sort_unstable();
+        assert_eq!(corner, vec![11, 19]);
+
+        let mut center: Vec<usize> = offset_neighbors(40, 9, &KNIGHT_OFFSETS).collect();
+        center.sort_unstable();
+        assert_eq!(center, vec![21, 23, 29, 33, 47, 51, 57, 59]);
+
+        // Knight moves don't wrap around the edge of the grid.
+        let mut edge: Vec<usize> = offset_neighbors(8, 9, &KNIGHT_OFFSETS).collect();
+        edge.sort_unstable();
+        assert_eq!(edge, vec![15, 25]);
+
+        assert_eq!(offset_neighbors(0, 1, &KNIGHT_OFFSETS).count(), 0);
+    }
+
+    #[test]
+    fn test_check_antiknight() {
+        let mut grid = [
+            ONE, TWO, THREE, FOUR, FOUR, THREE, TWO, ONE, THREE, FOUR, ONE, TWO, TWO, ONE, FOUR,
+            THREE,
+        ];
+        assert!(check_offsets(&KNIGHT_OFFSETS, 4, &grid));
+
+        grid = [
+            ONE, TWO, THREE, FOUR, THREE, FOUR, ONE, TWO, TWO, ONE, FOUR, THREE, FOUR, THREE, TWO,
+            ONE,
+        ];
+        assert!(!check_offsets(&KNIGHT_OFFSETS, 4, &grid));
+    }
+
+    #[test]
+    fn test_antiknight_enforce_consistency() {
+        let mut grid = [ALL_DIGITS; 81];
+        grid[40] = FIVE;
+        assert_eq!(
+            offsets_enforce_consistency(40, FIVE, &KNIGHT_OFFSETS, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        for i in [21, 23, 29, 33, 47, 51, 57, 59] {
+            assert_eq!(grid[i], ALL_DIGITS & !FIVE);
+        }
+        assert_eq!(grid[41], ALL_DIGITS);
+        assert_eq!(
+            offsets_enforce_consistency(40, FIVE, &KNIGHT_OFFSETS, 9, &mut grid),
+            Ok(Elimination::Same)
+        );
+
+        grid[21] = SIX;
+        assert!(offsets_enforce_consistency(40, SIX, &KNIGHT_OFFSETS, 9, &mut grid).is_err());
+    }
+
+    #[test]
+    fn test_check_nonconsecutive() {
+        let mut grid = [ONE, THREE, FIVE, ONE];
+        assert!(check_nonconsecutive(2, &grid));
+
+        grid[3] = FOUR;
+        assert!(!check_nonconsecutive(2, &grid));
+    }
+
+    #[test]
+    fn test_nonconsecutive_enforce_consistency() {
+        let mut grid = [ALL_DIGITS; 81];
+        grid[40] = FIVE;
+        assert_eq!(
+            nonconsecutive_enforce_consistency(40, FIVE, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        for i in [31, 39, 41, 49] {
+            assert_eq!(grid[i], ALL_DIGITS & !(FOUR | SIX));
+        }
+        assert_eq!(grid[30], ALL_DIGITS);
+
+        grid[0] = ONE;
+        grid[1] = TWO;
+        assert!(nonconsecutive_enforce_consistency(0, ONE, 9, &mut grid).is_err());
+    }
+
+    #[test]
+    fn test_related_by_any() {
+        assert_eq!(related_by_any(FIVE, ONE, differ_by), FOUR | SIX);
+        assert_eq!(
+            related_by_any(FIVE, ONE | THREE, differ_by),
+            TWO | FOUR | SIX | EIGHT
+        );
+        assert_eq!(related_by_any(ONE | TWO, TWO, differ_by), THREE | FOUR);
+        assert_eq!(related_by_any(TWO, TWO | THREE, ratio_of), ONE | FOUR | SIX);
+    }
+
+    #[test]
+    fn test_ratio_of() {
+        assert_eq!(ratio_of(ONE, 2), TWO);
+        assert_eq!(ratio_of(TWO, 2), ONE | FOUR);
+        assert_eq!(ratio_of(SIX, 3) & ALL_DIGITS, TWO);
+        assert_eq!(ratio_of(ONE | THREE, 3), THREE | NINE | ONE);
+        assert_eq!(ratio_of(FIVE | SEVEN, 2) & ALL_DIGITS, 0);
+    }
+
+    #[test]
+    fn test_ratio_enforce_consistency() {
+        let mut grid = [ALL_DIGITS, ALL_DIGITS];
+        assert_eq!(
+            ratio_enforce_consistency(0, 1, 2, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ONE | TWO | THREE | FOUR | SIX | EIGHT);
+        assert_eq!(grid[1], ONE | TWO | THREE | FOUR | SIX | EIGHT);
+
+        grid[0] = ONE | TWO;
+        grid[1] = ONE | TWO | FOUR;
+        assert_eq!(
+            ratio_enforce_consistency(0, 1, 3, &mut grid),
+            Err(Contradiction(()))
+        );
+
+        grid = [THREE | EIGHT, ONE | TWO | FOUR | SIX];
+        assert_eq!(
+            ratio_enforce_consistency(0, 1, 2, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid, [THREE | EIGHT, FOUR | SIX]);
+        assert!(check_ratio(0, 1, 2, &[THREE, SIX]));
+        assert!(!check_ratio(0, 1, 2, &[THREE, FOUR]));
+    }
+
+    #[test]
+    fn test_negative_ratio() {
+        let mut grid = [ALL_DIGITS; 4];
+        grid[0] = FOUR;
+        assert_eq!(
+            negative_ratio_enforce_consistency(0, FOUR, &[(0, 1)], TWO, 2, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[1], ALL_DIGITS);
+        assert_eq!(grid[2], ALL_DIGITS & !(TWO | EIGHT));
+
+        let grid = [THREE, SIX, ONE, FOUR];
+        assert!(check_negative_ratio(&[(0, 1)], TWO, 2, &grid));
+        assert!(!check_negative_ratio(&[], TWO, 2, &grid));
+        assert!(!check_negative_ratio(&[(0, 1)], TWO | FOUR, 2, &grid));
+    }
+
+    #[test]
+    fn test_check_difference() {
+        let grid = [TWO, FOUR, FIVE];
+        assert!(check_difference(0, 1, 2, &grid));
+        assert!(check_difference(1, 0, 2, &grid));
+        assert!(!check_difference(0, 1, 1, &grid));
+        assert!(check_difference(2, 1, 1, &grid));
+    }
+
+    #[test]
+    fn test_difference_enforce_consistency() {
+        let mut grid = [ALL_DIGITS, ALL_DIGITS];
+        assert_eq!(
+            difference_enforce_consistency(0, 1, 5, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(
+            grid[0],
+            ONE | TWO | THREE | FOUR | SIX | SEVEN | EIGHT | NINE
+        );
+        assert_eq!(
+            grid[1],
+            ONE | TWO | THREE | FOUR | SIX | SEVEN | EIGHT | NINE
+        );
+        assert_eq!(
+            difference_enforce_consistency(0, 1, 5, &mut grid),
+            Ok(Elimination::Same)
+        );
+
+        grid[0] = TWO;
+        assert_eq!(
+            difference_enforce_consistency(0, 1, 5, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[1], SEVEN);
+
+        grid[0] = FIVE;
+        assert!(difference_enforce_consistency(0, 1, 5, &mut grid).is_err());
+    }
+
+    #[test]
+    fn test_negative_difference() {
+        let mut grid = [ALL_DIGITS; 4];
+        grid[0] = THREE;
+        assert_eq!(
+            negative_difference_enforce_consistency(0, THREE, &[(0, 1)], ONE, 2, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[1], ALL_DIGITS);
+        assert_eq!(grid[2], ALL_DIGITS & !(TWO | FOUR));
+        assert_eq!(grid[3], ALL_DIGITS);
+
+        let grid = [THREE, FOUR, ONE, TWO];
+        assert!(check_negative_difference(&[(0, 1), (2, 3)], ONE, 2, &grid));
+        assert!(!check_negative_difference(&[(0, 1)], ONE, 2, &grid));
+        assert!(!check_negative_difference(
+            &[(0, 1), (2, 3)],
+            ONE | TWO,
+            2,
+            &grid
+        ));
+    }
+
+    #[test]
+    fn test_quad_enforce_consistency() {
+        let mut grid = [ALL_DIGITS; 81];
+        grid[0] = ONE;
+        assert_eq!(
+            quad_enforce_consistency(0, 1, ONE | TWO | THREE | FOUR, 0, 9, &mut grid),
+            Ok(Elimination::Same)
+        );
+
+        grid[0] = NINE;
+        assert!(quad_enforce_consistency(0, 0, ONE | TWO | THREE | FOUR, 0, 9, &mut grid).is_err());
+
+        grid[0] = FIVE;
+        grid[1] = FOUR;
+        assert!(
+            quad_enforce_consistency(0, 0, FIVE | SIX | SEVEN | EIGHT, 0, 9, &mut grid).is_err()
+        );
+
+        grid[9] = FOUR;
+        grid[10] = FIVE;
+        assert!(quad_enforce_consistency(0, 0, FIVE | SIX, 0, 9, &mut grid).is_err());
+        assert_eq!(
+            quad_enforce_consistency(0, 0, FOUR | FIVE, 0, 9, &mut grid),
+            Ok(Elimination::Same)
+        );
+    }
+
+    #[test]
+    fn test_sum_to() {
+        assert_eq!(sum_to(ONE, 10), NINE);
+        assert_eq!(
+            sum_to(ONE | TWO | THREE | FOUR, 5),
+            ONE | TWO | THREE | FOUR
+        );
+        assert_eq!(sum_to(FIVE | NINE, 10), ONE | FIVE);
+        assert_eq!(sum_to(FIVE | SIX, 5), 0);
+        assert_eq!(
+            related_by_any(ONE, (1 << 5) | (1 << 10), sum_to),
+            FOUR | NINE
+        );
+    }
+
+    #[test]
+    fn test_sum_enforce_consistency() {
+        let mut grid = [ALL_DIGITS, ALL_DIGITS];
+        assert_eq!(
+            sum_enforce_consistency(0, 1, 5, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ONE | TWO | THREE | FOUR);
+        assert_eq!(grid[1], ONE | TWO | THREE | FOUR);
+
+        grid[0] = THREE;
+        assert_eq!(
+            sum_enforce_consistency(0, 1, 5, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[1], TWO);
+        assert!(check_sum(0, 1, 5, &grid));
+        assert!(!check_sum(0, 1, 10, &grid));
+
+        let mut grid = [ALL_DIGITS, ALL_DIGITS];
+        assert_eq!(
+            sum_enforce_consistency(0, 1, 10, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ALL_DIGITS & !FIVE);
+    }
+
+    #[test]
+    fn test_cage_combinations() {
+        assert_eq!(cage_combinations(2, 3, 9), vec![ONE | TWO]);
+        assert_eq!(
+            cage_combinations(2, 10, 9),
+            vec![FOUR | SIX, THREE | SEVEN, TWO | EIGHT, ONE | NINE]
+        );
+        assert_eq!(cage_combinations(9, 45, 9), vec![ALL_DIGITS]);
+        assert!(cage_combinations(2, 18, 9).is_empty());
+        assert!(cage_combinations(2, 8, 4).is_empty());
+    }
 
     #[test]
-    fn test_check_quad() {
-        let mut grid = [0; 81];
-        grid[11] = FOUR;
-        grid[12] = TWO;
-        grid[20] = ONE;
-        grid[21] = THREE;
+    fn test_killer_enforce_consistency() {
+        let cage = [0, 1, 2];
+        let combinations = cage_combinations(3, 7, 9);
+        let mut grid = [ALL_DIGITS; 3];
+        assert_eq!(
+            killer_enforce_consistency(&cage, &combinations, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid, [ONE | TWO | FOUR; 3]);
 
-        let mut vals = ONE | TWO | THREE | FOUR;
-        assert!(check_quad(11, vals, 0, 9, &grid));
-        assert!(check_quad(11, TWO | THREE, 0, 9, &grid));
+        // A 3 rules out the only combination with a 1 and a 2, so only 1 + 3 + 6 or 2 + 3 + 5
+        // remain.
+        let combinations = cage_combinations(3, 10, 9);
+        let mut grid = [THREE, ALL_DIGITS, ALL_DIGITS];
+        assert_eq!(
+            killer_enforce_consistency(&cage, &combinations, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], THREE);
+        assert_eq!(grid[1], ONE | TWO | THREE | FIVE | SIX);
 
-        vals = TWO | THREE | FOUR | FIVE;
-        assert!(!check_quad(11, vals, 0, 9, &grid));
-        assert!(!check_quad(11, TWO | THREE, FOUR, 9, &grid));
+        let mut grid = [THREE, ONE, SIX];
+        assert!(killer_enforce_consistency(&cage, &combinations, &mut grid).is_ok());
+        assert!(check_killer(&cage, &combinations, &grid));
+        grid[2] = FIVE;
+        assert!(!check_killer(&cage, &combinations, &grid));
+        assert!(killer_enforce_consistency(&cage, &combinations, &mut grid).is_err());
     }
 
     #[test]
-    fn test_check_quad_repeated_digit_in_solution() {
-        let grid = [
-            32, 64, 8, 2, 16, 4, 4, 16, 2, 64, 32, 8, 16, 32, 4, 8, 64, 2, 8, 2, 64, 16, 4, 32, 64,
-            8, 32, 4, 2, 16, 2, 4, 16, 32, 8, 64,
-        ];
-        assert!(check_quad(4, THREE | FOUR, 0, 6, &grid));
-        assert!(check_quad(8, ONE | TWO | THREE | SIX, 0, 6, &grid));
-        assert!(check_quad(9, FIVE | SIX, 0, 6, &grid));
-        assert!(check_quad(12, ONE | THREE | FIVE, 0, 6, &grid));
-        assert!(check_quad(19, ONE | THREE | FIVE | SIX, 0, 6, &grid));
-        assert!(check_quad(22, ONE | FIVE, 0, 6, &grid));
+    fn test_thermo_enforce_consistency() {
+        let thermo = [0, 1, 2, 3];
+        let mut grid = [ALL_DIGITS; 4];
+        assert_eq!(
+            thermo_enforce_consistency(&thermo, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ONE | TWO | THREE | FOUR | FIVE | SIX);
+        assert_eq!(grid[1], TWO | THREE | FOUR | FIVE | SIX | SEVEN);
+        assert_eq!(grid[2], THREE | FOUR | FIVE | SIX | SEVEN | EIGHT);
+        assert_eq!(grid[3], FOUR | FIVE | SIX | SEVEN | EIGHT | NINE);
+
+        grid[1] = FIVE;
+        assert_eq!(
+            thermo_enforce_consistency(&thermo, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ONE | TWO | THREE | FOUR);
+        assert_eq!(grid[2], SIX | SEVEN | EIGHT);
+        assert_eq!(grid[3], SEVEN | EIGHT | NINE);
+        assert_eq!(
+            thermo_enforce_consistency(&thermo, &mut grid),
+            Ok(Elimination::Same)
+        );
+
+        assert!(check_thermo(&thermo, &[ONE, FIVE, SIX, NINE]));
+        assert!(!check_thermo(&thermo, &[ONE, SIX, FIVE, NINE]));
+
+        let mut grid = [FIVE, FOUR | FIVE];
+        assert!(thermo_enforce_consistency(&[0, 1], &mut grid).is_err());
     }
 
     #[test]
-    fn test_init_quad() {
-        let mut grid = [ALL_DIGITS; 81];
+    fn test_concatenate() {
+        assert_eq!(concatenate([1, 2].into_iter()), 12);
+        assert_eq!(concatenate([1, 2, 3].into_iter()), 123);
+        assert_eq!(concatenate([1, 16].into_iter()), 116);
+        assert_eq!(concatenate(std::iter::empty()), 0);
+    }
 
+    #[test]
+    fn test_arrow_enforce_consistency() {
+        // A three cell shaft sums to between 3 and 27.
+        let mut grid = [ALL_DIGITS; 5];
         assert_eq!(
-            init_quad(0, ONE | TWO | THREE | FOUR, 0, 9, &mut grid),
+            arrow_enforce_consistency(&[0], &[1, 2, 3], &mut grid),
             Ok(Elimination::Eliminated)
         );
-        assert_eq!(grid[0], ONE | TWO | THREE | FOUR);
+        assert_eq!(grid[0], ALL_DIGITS & !(ONE | TWO));
+        assert_eq!(grid[1], ONE | TWO | THREE | FOUR | FIVE | SIX | SEVEN);
 
-        grid[0] = NINE;
-        assert!(init_quad(0, ONE | TWO | THREE | FOUR, 0, 9, &mut grid).is_err());
+        grid[0] = FOUR;
+        assert_eq!(
+            arrow_enforce_consistency(&[0], &[1, 2], &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[1], ONE | TWO | THREE);
+        assert_eq!(grid[2], ONE | TWO | THREE);
 
-        grid[2] = FIVE;
-        grid[12] = FOUR;
-        assert!(init_quad(2, FIVE | SIX | SEVEN | EIGHT, 0, 9, &mut grid).is_err());
+        // A two cell circle over a three cell shaft is between 12 and 27.
+        let mut grid = [ALL_DIGITS; 5];
+        assert_eq!(
+            arrow_enforce_consistency(&[0, 1], &[2, 3, 4], &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ONE | TWO);
+        assert_eq!(grid[1], ALL_DIGITS);
+        assert_eq!(grid[2], ALL_DIGITS);
 
-        assert_eq!(init_quad(4, FIVE, SIX, 9, &mut grid), Ok(Elimination::Same));
+        grid[0] = TWO;
+        grid[1] = NINE;
+        assert!(arrow_enforce_consistency(&[0, 1], &[2, 3, 4], &mut grid).is_err());
 
-        grid[4] = FIVE;
-        grid[5] = SIX;
-        grid[13] = SIX;
-        grid[14] = FIVE;
-        assert!(init_quad(4, FOUR | FIVE, 0, 9, &mut grid).is_err());
+        assert!(check_arrow(&[0, 1], &[2, 3], &[ONE, TWO, FOUR, EIGHT]));
+        assert!(!check_arrow(&[0], &[1, 2], &[NINE, FOUR, FOUR]));
     }
 
     #[test]
-    fn test_quad_enforce_consistency() {
-        let mut grid = [ALL_DIGITS; 81];
-        grid[0] = ONE;
+    fn test_distinct_sum_range() {
+        assert_eq!(distinct_sum_range(0, 2, 8), Some((0, 0)));
+        assert_eq!(distinct_sum_range(2, 2, 8), Some((5, 15)));
+        assert_eq!(distinct_sum_range(7, 2, 8), Some((35, 35)));
+        assert_eq!(distinct_sum_range(8, 2, 8), None);
+    }
+
+    #[test]
+    fn test_sandwich_enforce_consistency() {
+        let line: Vec<usize> = (0..9).collect();
+
+        // Only 2 through 8 add up to 35, so the crusts are at the ends.
+        let mut grid = [ALL_DIGITS; 9];
         assert_eq!(
-            quad_enforce_consistency(0, 1, ONE | TWO | THREE | FOUR, 0, 9, &mut grid),
+            sandwich_enforce_consistency(&line, 35, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ALL_DIGITS);
+        assert_eq!(grid[8], ALL_DIGITS);
+        for g in &grid[1..8] {
+            assert_eq!(*g, ALL_DIGITS & !(ONE | NINE));
+        }
+
+        // A sum of 0 needs the crusts next to each other.
+        let mut grid = [ALL_DIGITS & !ONE; 9];
+        grid[4] = ONE;
+        assert_eq!(
+            sandwich_enforce_consistency(&line, 0, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        for (i, g) in grid.iter().enumerate() {
+            assert_eq!(*g & NINE != 0, i == 3 || i == 5);
+        }
+
+        let grid = [ONE, TWO, THREE, NINE, FOUR, FIVE, SIX, SEVEN, EIGHT];
+        assert!(check_sandwich(&line, 5, 9, &grid));
+        assert!(!check_sandwich(&line, 6, 9, &grid));
+    }
+
+    #[test]
+    fn test_little_killer_enforce_consistency() {
+        // 1 + 1 + 1 is the only way to make 3, since the digits can repeat.
+        let mut grid = [ALL_DIGITS; 3];
+        assert_eq!(
+            little_killer_enforce_consistency(&[0, 1, 2], 3, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid, [ONE; 3]);
+
+        let mut grid = [ALL_DIGITS; 3];
+        assert_eq!(
+            little_killer_enforce_consistency(&[0, 1, 2], 25, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid, [SEVEN | EIGHT | NINE; 3]);
+
+        let mut grid = [ALL_DIGITS, ALL_DIGITS, TWO];
+        assert_eq!(
+            little_killer_enforce_consistency(&[0, 1, 2], 6, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ONE | TWO | THREE);
+        assert!(little_killer_enforce_consistency(&[0, 1, 2], 30, &mut grid).is_err());
+
+        assert!(check_little_killer(&[0, 1], 3, &[ONE, TWO]));
+        assert!(!check_little_killer(&[0, 1], 4, &[ONE, TWO]));
+    }
+
+    #[test]
+    fn test_at_least_apart() {
+        assert_eq!(
+            at_least_apart(ONE, 5) & ALL_DIGITS,
+            SIX | SEVEN | EIGHT | NINE
+        );
+        assert_eq!(
+            at_least_apart(NINE, 5) & ALL_DIGITS,
+            ONE | TWO | THREE | FOUR
+        );
+        assert_eq!(at_least_apart(FIVE, 5) & ALL_DIGITS, 0);
+        assert_eq!(
+            at_least_apart(FOUR | SIX, 4) & ALL_DIGITS,
+            ONE | TWO | EIGHT | NINE
+        );
+    }
+
+    #[test]
+    fn test_whisper_enforce_consistency() {
+        let line = [0, 1, 2];
+        let mut grid = [ALL_DIGITS; 3];
+        assert_eq!(
+            whisper_enforce_consistency(&line, 5, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid, [ALL_DIGITS & !FIVE; 3]);
+
+        grid[1] = THREE;
+        assert_eq!(
+            whisper_enforce_consistency(&line, 5, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], EIGHT | NINE);
+        assert_eq!(grid[2], EIGHT | NINE);
+
+        assert!(check_whisper(&line, 5, &[NINE, THREE, EIGHT]));
+        assert!(!check_whisper(&line, 5, &[NINE, THREE, SEVEN]));
+    }
+
+    #[test]
+    fn test_renban_enforce_consistency() {
+        let line = [0, 1, 2, 3, 4];
+        let mut grid = [ALL_DIGITS; 5];
+        assert_eq!(
+            renban_enforce_consistency(&line, 9, &mut grid),
             Ok(Elimination::Same)
         );
 
-        grid[0] = NINE;
-        assert!(quad_enforce_consistency(0, 0, ONE | TWO | THREE | FOUR, 0, 9, &mut grid).is_err());
+        // With a 1 on the line, the only window is 1 to 5.
+        grid[2] = ONE;
+        assert_eq!(
+            renban_enforce_consistency(&line, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ONE | TWO | THREE | FOUR | FIVE);
+
+        // A 3 and a 7 fit in 3 to 7 only.
+        let mut grid = [THREE, ALL_DIGITS, SEVEN, ALL_DIGITS, ALL_DIGITS];
+        assert_eq!(
+            renban_enforce_consistency(&line, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[1], THREE | FOUR | FIVE | SIX | SEVEN);
+
+        let mut grid = [TWO, EIGHT, ALL_DIGITS];
+        assert!(renban_enforce_consistency(&[0, 1, 2], 9, &mut grid).is_err());
+
+        assert!(check_renban(&[0, 1, 2], &[FOUR, TWO, THREE]));
+        assert!(!check_renban(&[0, 1, 2], &[FOUR, TWO, FIVE]));
+        assert!(!check_renban(&[0, 1, 2], &[TWO, TWO, THREE]));
+    }
+
+    #[test]
+    fn test_equal_enforce_consistency() {
+        let pairs = [(0, 2), (1, 3)];
+        let mut grid = [ONE | TWO, ALL_DIGITS, TWO | THREE, ALL_DIGITS];
+        assert_eq!(
+            equal_enforce_consistency(&pairs, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid, [TWO, ALL_DIGITS, TWO, ALL_DIGITS]);
 
-        grid[0] = FIVE;
         grid[1] = FOUR;
-        assert!(
-            quad_enforce_consistency(0, 0, FIVE | SIX | SEVEN | EIGHT, 0, 9, &mut grid).is_err()
+        assert_eq!(
+            equal_enforce_consistency(&pairs, &mut grid),
+            Ok(Elimination::Eliminated)
         );
+        assert_eq!(grid[3], FOUR);
+        assert!(check_equal(&pairs, &grid));
+        grid[3] = FIVE;
+        assert!(!check_equal(&pairs, &grid));
 
-        grid[9] = FOUR;
-        grid[10] = FIVE;
-        assert!(quad_enforce_consistency(0, 0, FIVE | SIX, 0, 9, &mut grid).is_err());
+        grid[2] = THREE;
+        assert!(equal_enforce_consistency(&pairs, &mut grid).is_err());
+    }
+
+    #[test]
+    fn test_between_enforce_consistency() {
+        let line = [0, 1, 2, 3];
+        let mut grid = [ALL_DIGITS; 4];
         assert_eq!(
-            quad_enforce_consistency(0, 0, FOUR | FIVE, 0, 9, &mut grid),
+            between_enforce_consistency(&line, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ALL_DIGITS);
+        assert_eq!(grid[1], ALL_DIGITS & !(ONE | NINE));
+
+        grid[0] = THREE;
+        grid[3] = SEVEN | EIGHT;
+        assert_eq!(
+            between_enforce_consistency(&line, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[1], FOUR | FIVE | SIX | SEVEN);
+
+        grid[1] = SEVEN;
+        assert_eq!(
+            between_enforce_consistency(&line, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[3], EIGHT);
+
+        let mut grid = [FOUR, ALL_DIGITS, FIVE];
+        assert!(between_enforce_consistency(&[0, 1, 2], &mut grid).is_err());
+
+        assert!(check_between(&line, &[THREE, SEVEN, FOUR, EIGHT]));
+        assert!(check_between(&line, &[EIGHT, SEVEN, FOUR, THREE]));
+        assert!(!check_between(&line, &[THREE, SEVEN, THREE, EIGHT]));
+    }
+
+    #[test]
+    fn test_lockout_enforce_consistency() {
+        let line = [0, 1, 2];
+        let mut grid = [FIVE, ALL_DIGITS, ALL_DIGITS];
+        assert_eq!(
+            lockout_enforce_consistency(&line, 4, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[2], ONE | NINE);
+        assert_eq!(grid[1], ALL_DIGITS & !FIVE);
+
+        grid[2] = NINE;
+        assert_eq!(
+            lockout_enforce_consistency(&line, 4, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[1], ONE | TWO | THREE | FOUR);
+
+        assert!(check_lockout(&line, 4, &[FIVE, TWO, NINE]));
+        assert!(!check_lockout(&line, 4, &[FIVE, SIX, NINE]));
+        assert!(!check_lockout(&line, 4, &[FIVE, TWO, EIGHT]));
+    }
+
+    #[test]
+    fn test_xsum_enforce_consistency() {
+        let line: Vec<usize> = (0..9).collect();
+        let mut grid = [ALL_DIGITS; 9];
+        // A sum of 3 can only be a 2 followed by a 1.
+        assert_eq!(
+            xsum_enforce_consistency(&line, 3, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], TWO);
+
+        let mut grid = [ALL_DIGITS; 9];
+        grid[0] = ALL_DIGITS & !TWO;
+        assert!(xsum_enforce_consistency(&line, 3, 9, &mut grid).is_err());
+
+        // The biggest sum for 2 cells is 2 + 9, and the smallest for 6 cells is 6 + 1 + 2 + 3 + 4 + 5.
+        let mut grid = [ALL_DIGITS; 9];
+        assert_eq!(
+            xsum_enforce_consistency(&line, 20, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], THREE | FOUR | FIVE);
+
+        let mut grid = [ALL_DIGITS; 9];
+        grid[0] = THREE;
+        assert_eq!(
+            xsum_enforce_consistency(&line, 20, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[1], EIGHT | NINE);
+        assert_eq!(grid[2], EIGHT | NINE);
+
+        let solved = [THREE, EIGHT, NINE, ONE, TWO, FOUR, FIVE, SIX, SEVEN];
+        assert!(check_xsum(&line, 20, &solved));
+        assert!(!check_xsum(&line, 12, &solved));
+    }
+
+    #[test]
+    fn test_skyscraper_enforce_consistency() {
+        let line: Vec<usize> = (0..9).collect();
+        let mut grid = [ALL_DIGITS; 9];
+        assert_eq!(
+            skyscraper_enforce_consistency(&line, 1, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], NINE);
+
+        // To see all 9, the digits have to go up in order.
+        let mut grid = [ALL_DIGITS; 9];
+        assert_eq!(
+            skyscraper_enforce_consistency(&line, 9, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ONE);
+        assert_eq!(grid[4], FIVE);
+        assert_eq!(grid[8], NINE);
+
+        // Seeing 3 means the first cell is at most a 7, and the second is at most an 8.
+        let mut grid = [ALL_DIGITS; 9];
+        assert_eq!(
+            skyscraper_enforce_consistency(&line, 3, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[0], ALL_DIGITS & !(EIGHT | NINE));
+        assert_eq!(grid[1], ALL_DIGITS & !NINE);
+        assert_eq!(grid[2], ALL_DIGITS);
+
+        let solved = [THREE, EIGHT, NINE, ONE, TWO, FOUR, FIVE, SIX, SEVEN];
+        assert!(check_skyscraper(&line, 3, &solved));
+        assert!(!check_skyscraper(&line, 2, &solved));
+    }
+
+    #[test]
+    fn test_classes() {
+        assert_eq!(
+            entropic_classes(9),
+            [ONE | TWO | THREE, FOUR | FIVE | SIX, SEVEN | EIGHT | NINE]
+        );
+        assert_eq!(
+            modular_classes(9),
+            [THREE | SIX | NINE, ONE | FOUR | SEVEN, TWO | FIVE | EIGHT]
+        );
+        assert_eq!(entropic_classes(6), [ONE | TWO, THREE | FOUR, FIVE | SIX]);
+    }
+
+    #[test]
+    fn test_classes_enforce_consistency() {
+        let classes = entropic_classes(9);
+        let line = [0, 1, 2, 3, 4];
+        let mut grid = [ALL_DIGITS; 5];
+        assert_eq!(
+            classes_enforce_consistency(&line, &classes, &mut grid),
             Ok(Elimination::Same)
         );
+
+        grid[0] = TWO;
+        grid[4] = FIVE | EIGHT;
+        assert_eq!(
+            classes_enforce_consistency(&line, &classes, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[3], ONE | TWO | THREE);
+        assert_eq!(grid[1], FOUR | FIVE | SIX | SEVEN | EIGHT | NINE);
+        assert_eq!(grid[2], FOUR | FIVE | SIX | SEVEN | EIGHT | NINE);
+
+        grid[1] = SIX;
+        assert_eq!(
+            classes_enforce_consistency(&line, &classes, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[2], SEVEN | EIGHT | NINE);
+        assert_eq!(grid[4], FIVE);
+
+        let mut grid = [ONE, TWO, THREE, FOUR, FIVE];
+        assert!(check_classes(&line, &modular_classes(9), &grid));
+        grid[3] = THREE;
+        assert!(!check_classes(&line, &modular_classes(9), &grid));
+        grid[2] = ONE;
+        assert!(classes_enforce_consistency(&line, &classes, &mut grid).is_err());
+    }
+
+    #[test]
+    fn test_region_segments() {
+        let regions = vec![vec![0, 1, 4, 5], vec![2, 3, 6, 7]];
+        let line = [0, 1, 2, 6, 5];
+        assert_eq!(
+            region_segments(&line, &regions),
+            vec![&[0, 1][..], &[2, 6][..], &[5][..]]
+        );
+    }
+
+    #[test]
+    fn test_region_sum_enforce_consistency() {
+        let segments: [&[usize]; 2] = [&[0], &[1, 2]];
+        let mut grid = [ALL_DIGITS; 3];
+        assert_eq!(
+            region_sum_enforce_consistency(&segments, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        // Two different digits add up to at least 3.
+        assert_eq!(grid[0], ALL_DIGITS & !(ONE | TWO));
+        assert_eq!(grid[1], ALL_DIGITS & !NINE);
+
+        grid[0] = FOUR;
+        assert_eq!(
+            region_sum_enforce_consistency(&segments, 9, &mut grid),
+            Ok(Elimination::Eliminated)
+        );
+        assert_eq!(grid[1], ONE | TWO | THREE);
+
+        assert!(check_region_sum(&segments, &[FOUR, ONE, THREE]));
+        assert!(!check_region_sum(&segments, &[FOUR, ONE, TWO]));
     }
 }
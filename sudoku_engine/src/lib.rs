@@ -3,11 +3,14 @@
 #![warn(missing_docs)]
 mod board;
 mod constraints;
+mod logical;
 mod types;
 
 use types::Bits;
 pub use types::Board;
+pub use types::Deductions;
 use types::Elimination;
+pub use types::ExtraLine;
 pub use types::SudokuErrors;
 
 /// Convert a string of digits into the associated Board.
@@ -112,7 +115,7 @@ pub fn eliminate(board: &mut Board, idx: usize, value: usize) -> Result<Eliminat
 pub fn solve(board: &Board) -> Result<Board, SudokuErrors> {
     let mut slns = board.solutions();
     let Some(result) = slns.next() else {
-        return Err(SudokuErrors::Contradiction)
+        return Err(SudokuErrors::Contradiction);
     };
 
     match slns.next() {
@@ -0,0 +1,340 @@
+//! A logical solver that applies one human style technique at a time, and describes each
+//! deduction it makes.
+
+use crate::board::idx_to_rc;
+use crate::types::{Board, BoardMeta, Contradiction};
+use tokio_util::sync::CancellationToken;
+
+/// A human readable name for a unit, from its index in `Board::units`.
+pub(crate) fn unit_name(meta: &BoardMeta, unit: usize) -> String {
+    let size = meta.size;
+    if unit < size {
+        format!("row {}", unit + 1)
+    } else if unit < 2 * size {
+        format!("column {}", unit - size + 1)
+    } else if unit < 2 * size + meta.regions.len() {
+        format!("box {}", unit - 2 * size + 1)
+    } else {
+        "extra region".to_string()
+    }
+}
+
+impl Board {
+    fn naked_single_step(&mut self) -> Result<Option<String>, Contradiction> {
+        let Some(idx) =
+            (0..self.len()).find(|i| !self.solved_digits[*i] && self.grid[*i].count_ones() == 1)
+        else {
+            return Ok(None);
+        };
+        let value = self.grid[idx];
+        self.assign(idx, value)?;
+        Ok(Some(format!(
+            "Naked single {} at {}",
+            value.trailing_zeros(),
+            idx_to_rc(idx, self.meta.size)
+        )))
+    }
+
+    fn hidden_single_step(&mut self) -> Result<Option<String>, Contradiction> {
+        let meta = self.meta.clone();
+        for (u, unit) in Board::units(&meta).into_iter().enumerate() {
+            for i in 1..=meta.max_val {
+                let v = 1 << i;
+                if unit
+                    .iter()
+                    .any(|idx| self.solved_digits[*idx] && self.grid[*idx] == v)
+                {
+                    continue;
+                }
+                let mut f = unit.iter().filter(|idx| self.grid[**idx] & v != 0);
+                match (f.next(), f.next()) {
+                    (None, _) => return Err(Contradiction(())),
+                    (Some(idx), None) => {
+                        let idx = *idx;
+                        self.assign(idx, v)?;
+                        return Ok(Some(format!(
+                            "Hidden single {i} in {} at {}",
+                            unit_name(&meta, u),
+                            idx_to_rc(idx, meta.size)
+                        )));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn locked_candidates_step(&mut self) -> Result<Option<String>, Contradiction> {
+        let meta = self.meta.clone();
+        for o in &meta.overlapping_units {
+            for i in 1..=meta.max_val {
+                let v = 1 << i;
+                let candidate = |idx: &usize| !self.solved_digits[*idx] && self.grid[*idx] & v != 0;
+                if !o.shared.iter().any(candidate) || o.rest.iter().any(candidate) {
+                    continue;
+                }
+                let removed: Vec<usize> = o
+                    .other_rest
+                    .iter()
+                    .filter(|idx| self.grid[**idx] & v != 0)
+                    .copied()
+                    .collect();
+                if removed.is_empty() {
+                    continue;
+                }
+                for idx in &removed {
+                    self.eliminate(*idx, v)?;
+                }
+                // Rows and columns come first in the list of units.
+                let technique = if o.unit < 2 * meta.size {
+                    "Claiming"
+                } else {
+                    "Pointing"
+                };
+                let cells: Vec<String> = removed
+                    .iter()
+                    .map(|idx| idx_to_rc(*idx, meta.size))
+                    .collect();
+                return Ok(Some(format!(
+                    "{technique} {i} in {} removes it from {} at {}",
+                    unit_name(&meta, o.unit),
+                    unit_name(&meta, o.other),
+                    cells.join(", ")
+                )));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn fish_step(&mut self, n: usize, finned: bool) -> Result<Option<String>, Contradiction> {
+        let Some(fish) = self.find_fish(n, finned) else {
+            return Ok(None);
+        };
+        for idx in &fish.eliminations {
+            self.eliminate(*idx, 1 << fish.digit)?;
+        }
+
+        let meta = self.meta.clone();
+        let names = |units: &[usize]| -> String {
+            units
+                .iter()
+                .map(|u| unit_name(&meta, *u))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let cells = |cells: &[usize]| -> String {
+            cells
+                .iter()
+                .map(|idx| idx_to_rc(*idx, meta.size))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let name = match n {
+            2 => "X-Wing".to_string(),
+            3 => "Swordfish".to_string(),
+            4 => "Jellyfish".to_string(),
+            _ => format!("Size {n} fish"),
+        };
+        let (finned, fins) = if fish.fins.is_empty() {
+            ("", String::new())
+        } else {
+            ("Finned ", format!(" with fins at {}", cells(&fish.fins)))
+        };
+        Ok(Some(format!(
+            "{finned}{name} on {} in {} covered by {}{fins} removes it from {}",
+            fish.digit,
+            names(&fish.bases),
+            names(&fish.covers),
+            cells(&fish.eliminations)
+        )))
+    }
+
+    /// Apply the simplest logical technique that makes progress on the puzzle, and return a
+    /// description of the deduction. Returns `None` if no technique applies.
+    ///
+    /// # Errors
+    /// This will throw an error if the puzzle is found to be broken.
+    pub fn logical_step(&mut self) -> Result<Option<String>, Contradiction> {
+        if let Some(step) = self.naked_single_step()? {
+            return Ok(Some(step));
+        }
+        if let Some(step) = self.hidden_single_step()? {
+            return Ok(Some(step));
+        }
+        if self.meta.deductions.locked_candidates {
+            if let Some(step) = self.locked_candidates_step()? {
+                return Ok(Some(step));
+            }
+        }
+        if self.meta.deductions.fish_steps {
+            // Any basic fish is simpler than a finned one.
+            for finned in [false, true] {
+                for n in 2..=(self.meta.size / 2).min(4) {
+                    if let Some(step) = self.fish_step(n, finned)? {
+                        return Ok(Some(step));
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Repeatedly apply logical steps until the puzzle is solved, or no more progress can be made.
+    /// A description of each deduction is appended to `steps`, including those made before any
+    /// contradiction is found.
+    ///
+    /// # Errors
+    /// This will throw an error if the puzzle is found to be broken.
+    pub fn logical_solve(
+        &mut self,
+        token: &CancellationToken,
+        steps: &mut Vec<String>,
+    ) -> Result<(), Contradiction> {
+        while !token.is_cancelled() {
+            match self.logical_step()? {
+                Some(step) => steps.push(step),
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::from_string;
+    use crate::types::Board;
+    use tokio_util::sync::CancellationToken;
+
+    #[test]
+    fn naked_single() {
+        let mut board = from_string(
+            "12345678.........................................................................",
+        )
+        .unwrap();
+        assert_eq!(
+            board.logical_step(),
+            Ok(Some("Naked single 9 at R1C9".to_string()))
+        );
+        assert_eq!(board.digits()[8], 9);
+    }
+
+    #[test]
+    fn hidden_single() {
+        let mut board = from_string(
+            ".........5...........5............5...........................5..................",
+        )
+        .unwrap();
+        // 5 is excluded from boxes 1 and 2 in row 1, and from columns 8 and 9.
+        assert_eq!(
+            board.logical_step(),
+            Ok(Some("Hidden single 5 in row 1 at R1C7".to_string()))
+        );
+        assert_eq!(board.digits()[6], 5);
+    }
+
+    #[test]
+    fn locked_candidates() {
+        let mut board = Board::new(9, 9).unwrap();
+        for idx in [9, 10, 11, 18, 19, 20] {
+            board.eliminate(idx, 1 << 1).unwrap();
+        }
+        for idx in 3..9 {
+            board.eliminate(idx, 1 << 2).unwrap();
+        }
+        assert_eq!(
+            board.logical_step(),
+            Ok(Some(
+                "Claiming 2 in row 1 removes it from box 1 at R2C1, R2C2, R2C3, R3C1, R3C2, R3C3"
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            board.logical_step(),
+            Ok(Some(
+                "Pointing 1 in box 1 removes it from row 1 at R1C4, R1C5, R1C6, R1C7, R1C8, R1C9"
+                    .to_string()
+            ))
+        );
+        assert_eq!(board.logical_step(), Ok(None));
+    }
+
+    #[test]
+    fn fish() {
+        let mut board = Board::new(9, 9).unwrap();
+        for idx in [1, 2, 3, 5, 6, 7, 8, 37, 38, 39, 41, 42, 43, 44] {
+            board.eliminate(idx, 1 << 1).unwrap();
+        }
+        assert_eq!(
+            board.logical_step(),
+            Ok(Some(
+                "X-Wing on 1 in row 1, row 5 covered by column 1, column 5 removes it from R2C1, \
+                 R2C5, R3C1, R3C5, R4C1, R4C5, R6C1, R6C5, R7C1, R7C5, R8C1, R8C5, R9C1, R9C5"
+                    .to_string()
+            ))
+        );
+
+        let mut board = Board::new(9, 9).unwrap();
+        for idx in [1, 2, 3, 5, 6, 7, 8, 37, 38, 39, 42, 43, 44] {
+            board.eliminate(idx, 1 << 1).unwrap();
+        }
+        assert_eq!(
+            board.logical_step(),
+            Ok(Some(
+                "Finned X-Wing on 1 in row 1, row 5 covered by column 1, column 5 with fins at \
+                 R5C6 removes it from R4C5, R6C5"
+                    .to_string()
+            ))
+        );
+
+        // A basic X-Wing on a later digit comes before the finned one.
+        let mut board = Board::new(9, 9).unwrap();
+        for idx in [1, 2, 3, 5, 6, 7, 8, 37, 38, 39, 42, 43, 44] {
+            board.eliminate(idx, 1 << 1).unwrap();
+        }
+        for idx in [1, 2, 3, 5, 6, 7, 8, 37, 38, 39, 41, 42, 43, 44] {
+            board.eliminate(idx, 1 << 2).unwrap();
+        }
+        assert_eq!(
+            board.logical_step(),
+            Ok(Some(
+                "X-Wing on 2 in row 1, row 5 covered by column 1, column 5 removes it from R2C1, \
+                 R2C5, R3C1, R3C5, R4C1, R4C5, R6C1, R6C5, R7C1, R7C5, R8C1, R8C5, R9C1, R9C5"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn solve_path() {
+        let repr =
+            "19..7..5....28..........37.2.5.....4...4.5.....6.....9731....2....82.....4....91.";
+        let mut board = from_string(repr).unwrap();
+        let token = CancellationToken::new();
+        let mut steps = Vec::new();
+        assert!(board.logical_solve(&token, &mut steps).is_ok());
+        assert!(board.solved());
+
+        // Every step places exactly one digit.
+        let givens = repr.chars().filter(|c| *c != '.').count();
+        assert_eq!(steps.len(), 81 - givens);
+    }
+
+    #[test]
+    fn solve_path_cancelled() {
+        let mut board = from_string(
+            "19..7..5....28..........37.2.5.....4...4.5.....6.....9731....2....82.....4....91.",
+        )
+        .unwrap();
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut steps = Vec::new();
+        assert!(board.logical_solve(&token, &mut steps).is_ok());
+        assert!(steps.is_empty());
+    }
+}
@@ -33,6 +33,9 @@ pub enum SudokuErrors {
     /// An irregular
     IrregularWrongSizes,
 
+    /// A clone's two groups of cells have different lengths.
+    CloneWrongSizes,
+
     /// A puzzle has multiple solutions. Used as an error in cases where a unique solution is
     /// expected.
     MultipleSolutions,
@@ -116,13 +119,126 @@ pub(crate) const DIMENSIONS: [(usize, usize); MAX_SIZE] = [
     (4, 4),
 ];
 
+/// A full row or column of the grid, used by clues that are given outside of it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Line {
+    Row(usize),
+    Column(usize),
+}
+
+/// A row or column, read starting from the end an outside clue is on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Sightline {
+    pub(crate) line: Line,
+    pub(crate) reversed: bool,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) enum Constraint {
     Quad(usize, Bits, Bits),
     Region(Vec<usize>),
+    AntiKnight,
+    AntiKing,
+    Nonconsecutive,
+    Difference(usize, usize, usize),
+    NegativeDifference(Vec<(usize, usize)>, Bits),
+    Ratio(usize, usize, usize),
+    NegativeRatio(Vec<(usize, usize)>, Bits),
+    Sum(usize, usize, usize),
+    NegativeSum(Vec<(usize, usize)>, Bits),
+    Killer(Vec<usize>, Vec<Bits>),
+    Thermo(Vec<usize>),
+    Arrow(Vec<usize>, Vec<usize>),
+    Sandwich(Line, usize),
+    LittleKiller(Vec<usize>, usize),
+    Whisper(Vec<usize>, usize),
+    Renban(Vec<usize>),
+    Equal(Vec<(usize, usize)>),
+    Between(Vec<usize>),
+    Lockout(Vec<usize>, usize),
+    XSum(Sightline, usize),
+    Skyscraper(Sightline, usize),
+    Entropic(Vec<usize>),
+    Modular(Vec<usize>),
+    RegionSum(Vec<usize>),
+}
+
+/// Lines that f-puzzles has no object for, so they aren't imported. Add them to a board with
+/// `Board::add_line` instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExtraLine {
+    /// The digits on the line are outside of the range of the two ends, and the ends differ by at
+    /// least the given amount.
+    Lockout(usize),
+
+    /// Every run of 3 cells has a low, a middle and a high digit.
+    Entropic,
+
+    /// Every run of 3 cells has a digit of each remainder when divided by 3.
+    Modular,
+
+    /// The line adds up to the same total in each region it passes through.
+    RegionSum,
+}
+
+/// The techniques that are used to narrow down candidates between guesses, on top of naked and
+/// hidden singles. Tuples are looked for from pairs up to the largest size given, and a size below
+/// 2 turns them off.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Deductions {
+    /// Use pointing and claiming.
+    pub locked_candidates: bool,
+
+    /// The largest naked tuple to look for.
+    pub naked_tuples: usize,
+
+    /// The largest hidden tuple to look for.
+    pub hidden_tuples: usize,
+
+    /// The largest fish to look for. 2 is an X-Wing, 3 a Swordfish and 4 a Jellyfish. Finned fish
+    /// are only used by the step engine.
+    pub fish: usize,
+
+    /// Let fish use regions as base and cover sets, as well as rows and columns.
+    pub fish_regions: bool,
+
+    /// Let the step engine look for fish, basic or finned, up to Jellyfish. This is separate from
+    /// `fish`, as a human solver expects them even where they are too slow for guessing.
+    pub fish_steps: bool,
 }
 
-#[derive(Debug)]
+/// On `hardest.txt` in `sudoku_solver/data`, pointing alone solves fastest. Naked pairs take about
+/// 1.5 times as long and hidden pairs about 1.8 times, while larger tuples and fish are slower
+/// still. On the larger files, either kind of pair is within about 10% of pointing alone. So they
+/// are off by default. Use `sudoku_solver benchmark` to compare.
+impl Default for Deductions {
+    fn default() -> Self {
+        Deductions {
+            locked_candidates: true,
+            naked_tuples: 0,
+            hidden_tuples: 0,
+            fish: 0,
+            fish_regions: false,
+            fish_steps: true,
+        }
+    }
+}
+
+/// Two units that share more than one cell, split into the shared cells and the rest of each unit.
+#[derive(Clone, Debug)]
+pub(crate) struct Overlap {
+    /// The index of the first unit in `Board::units`.
+    pub(crate) unit: usize,
+
+    /// The index of the second unit in `Board::units`.
+    pub(crate) other: usize,
+
+    pub(crate) shared: Vec<usize>,
+    pub(crate) rest: Vec<usize>,
+    pub(crate) other_rest: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
 pub(crate) struct BoardMeta {
     /// The size of a side of the board.
     pub(crate) size: usize,
@@ -138,6 +254,36 @@ pub(crate) struct BoardMeta {
     pub(crate) regions: Vec<Vec<usize>>,
 
     pub(crate) constraints: Vec<Constraint>,
+
+    /// Pairs of units that share more than one cell.
+    pub(crate) overlapping_units: Vec<Overlap>,
+
+    /// Each of `Board::units` as a set of cells, in the same order.
+    pub(crate) unit_cells: Vec<MoreBits>,
+
+    /// The cells that share a unit with each cell, including the cell itself.
+    pub(crate) peers: Vec<MoreBits>,
+
+    pub(crate) deductions: Deductions,
+}
+
+impl BoardMeta {
+    /// The cells of a row or column, in order.
+    pub(crate) fn line(&self, line: Line) -> &[usize] {
+        match line {
+            Line::Row(r) => &self.rows[r],
+            Line::Column(c) => &self.columns[c],
+        }
+    }
+
+    /// The cells of a row or column, in the order they are seen from an outside clue.
+    pub(crate) fn sightline(&self, sightline: Sightline) -> Vec<usize> {
+        let mut cells = self.line(sightline.line).to_vec();
+        if sightline.reversed {
+            cells.reverse();
+        }
+        cells
+    }
 }
 
 /// A representation of a sudoku board.
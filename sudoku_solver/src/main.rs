@@ -6,6 +6,8 @@ use solution_iter::{true_candidates_bfs, SolutionIterator};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
+use std::time::Instant;
+use sudoku_engine::Deductions;
 use tokio_util::sync::CancellationToken;
 use yy_engine::YinYang;
 
@@ -26,6 +28,9 @@ enum Command {
     /// Treat each line of a file as an individual puzzle, and solve all of them.
     FromFile { path: PathBuf },
 
+    /// Time solving every puzzle in a file, with each of a few choices of deductions.
+    Benchmark { path: PathBuf },
+
     BuildIrregular {
         size: usize,
         out_file: PathBuf,
@@ -84,6 +89,110 @@ fn solve_file(path: &Path) {
     }
 }
 
+/// The deductions that are compared by the benchmark, and a name for each of them. Each is timed
+/// on its own, not on top of the ones before it.
+fn benchmarks() -> [(&'static str, Deductions); 8] {
+    // The default is pointing, with nothing else used between guesses.
+    let pointing = Deductions::default();
+    [
+        (
+            "singles",
+            Deductions {
+                locked_candidates: false,
+                ..pointing
+            },
+        ),
+        ("pointing", pointing),
+        (
+            "pointing and naked pairs, no hidden tuples",
+            Deductions {
+                naked_tuples: 2,
+                ..pointing
+            },
+        ),
+        (
+            "pointing and hidden pairs, no naked tuples",
+            Deductions {
+                hidden_tuples: 2,
+                ..pointing
+            },
+        ),
+        (
+            "pointing and naked and hidden tuples up to triples",
+            Deductions {
+                naked_tuples: 3,
+                hidden_tuples: 3,
+                ..pointing
+            },
+        ),
+        (
+            "pointing and naked and hidden tuples up to quads",
+            Deductions {
+                naked_tuples: 4,
+                hidden_tuples: 4,
+                ..pointing
+            },
+        ),
+        (
+            "pointing and basic fish up to jellyfish",
+            Deductions {
+                fish: 4,
+                ..pointing
+            },
+        ),
+        (
+            "pointing and basic fish up to jellyfish, with regions",
+            Deductions {
+                fish: 4,
+                fish_regions: true,
+                ..pointing
+            },
+        ),
+    ]
+}
+
+/// Solve every puzzle in the file once with each of `benchmarks`, and print the total time each
+/// took, along with how many puzzles it failed to solve. Only the solves are timed, not reading
+/// the file. Puzzles that can't be solved with the default deductions, such as ones with more
+/// than one solution, are left out so that only successful solves are timed.
+fn benchmark(path: &Path) {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            return;
+        }
+    };
+    let mut puzzles = Vec::new();
+    for (i, line) in data.lines().enumerate() {
+        match sudoku_engine::from_string(line).and_then(|board| {
+            sudoku_engine::solve(&board)?;
+            Ok(board)
+        }) {
+            Ok(board) => puzzles.push(board),
+            Err(e) => eprintln!("Skipping line {}: {e}", i + 1),
+        }
+    }
+
+    for (name, deductions) in benchmarks() {
+        let start = Instant::now();
+        let mut failures = 0;
+        for puzzle in &puzzles {
+            let mut board = puzzle.clone();
+            board.set_deductions(deductions);
+            if sudoku_engine::solve(&board).is_err() {
+                failures += 1;
+            }
+        }
+        let elapsed = start.elapsed();
+        if failures == 0 {
+            println!("{name}: {elapsed:?}");
+        } else {
+            println!("{name}: {elapsed:?}, {failures} failed");
+        }
+    }
+}
+
 fn solve_yin_yang<R: std::io::BufRead, W: std::io::Write, W2: std::io::Write>(
     computation: &YyComputation,
     file: R,
@@ -173,6 +282,7 @@ fn main() {
     match args.cmd {
         Command::Solve { repr } => solve_puzzle(&repr),
         Command::FromFile { path } => solve_file(&path),
+        Command::Benchmark { path } => benchmark(&path),
         Command::BuildIrregular {
             size,
             out_file,
@@ -456,9 +456,7 @@ impl YinYang {
         if !self.check_helper(&mut shaded, &mut shaded_queue, 1, 0) {
             return false;
         }
-        for v in &mut shaded {
-            *v = false;
-        }
+        shaded.fill(false);
         self.check_helper(&mut shaded, &mut shaded_queue, 2, 0) && self.check_two_by_two()
     }
 